@@ -0,0 +1,114 @@
+use bevy::{app::Events, prelude::*};
+use bevy_kira_audio::{Audio, AudioPlugin, AudioSource};
+
+// Gameplay systems send these instead of talking to the audio backend,
+// so the game runs the same with or without AudioFeedbackPlugin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioCue {
+    Szunami,
+    Homescreen,
+    Move,
+    Apple,
+    Enter,
+    ChangeSelection,
+    Win,
+    Stop,
+}
+
+struct MaybeSoundAssets(Option<SoundAssets>);
+
+struct SoundAssets {
+    snake_move: Handle<AudioSource>,
+    szunami: Handle<AudioSource>,
+    apple: Handle<AudioSource>,
+    enter: Handle<AudioSource>,
+    changeselection: Handle<AudioSource>,
+    win: Handle<AudioSource>,
+    homescreen: Handle<AudioSource>,
+}
+
+pub struct AudioFeedbackPlugin;
+
+impl Plugin for AudioFeedbackPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        add_audio_cues(app);
+
+        app.add_plugin(AudioPlugin)
+            .insert_resource(MaybeSoundAssets(None))
+            .add_startup_system(load_sounds.system())
+            .add_system(play_cues.system());
+    }
+}
+
+// both the game and the audio plugin need the event; only register it once,
+// a second update system would drop cues before they are read.
+pub(crate) fn add_audio_cues(app: &mut AppBuilder) {
+    if app.world().get_resource::<Events<AudioCue>>().is_none() {
+        app.add_event::<AudioCue>();
+    }
+}
+
+fn load_sounds(asset_server: Res<AssetServer>, mut sound_assets: ResMut<MaybeSoundAssets>) {
+    dbg!("loading sounds");
+    let snake_move = asset_server.load("sounds/move.wav");
+    let szunami = asset_server.load("sounds/szunami.wav");
+    let apple = asset_server.load("sounds/apple.wav");
+    let enter = asset_server.load("sounds/enter.wav");
+    let changeselection = asset_server.load("sounds/changeselection.wav");
+    let win = asset_server.load("sounds/win.wav");
+    let homescreen = asset_server.load("sounds/homescreen.wav");
+    *sound_assets = MaybeSoundAssets(Some(SoundAssets {
+        snake_move,
+        szunami,
+        apple,
+        enter,
+        changeselection,
+        win,
+        homescreen,
+    }));
+}
+
+fn play_cues(
+    mut cues: EventReader<AudioCue>,
+    maybe_sounds: Res<MaybeSoundAssets>,
+    audio: Res<Audio>,
+) {
+    let sounds = match &maybe_sounds.0 {
+        Some(sounds) => sounds,
+        None => {
+            for _cue in cues.iter() {
+                dbg!("No sounds yet.");
+            }
+            return;
+        }
+    };
+
+    for cue in cues.iter() {
+        match cue {
+            AudioCue::Szunami => {
+                audio.play(sounds.szunami.clone_weak());
+            }
+            AudioCue::Homescreen => {
+                audio.play(sounds.homescreen.clone_weak());
+            }
+            AudioCue::Move => {
+                audio.play(sounds.snake_move.clone_weak());
+            }
+            AudioCue::Apple => {
+                audio.play(sounds.apple.clone_weak());
+            }
+            AudioCue::Enter => {
+                audio.play(sounds.enter.clone_weak());
+            }
+            AudioCue::ChangeSelection => {
+                audio.play(sounds.changeselection.clone_weak());
+            }
+            AudioCue::Win => {
+                audio.play_looped(sounds.win.clone_weak());
+            }
+            AudioCue::Stop => {
+                audio.stop();
+            }
+        }
+    }
+}
@@ -0,0 +1,401 @@
+// Keyboard and gamepad controls.
+//
+// Every screen reads `Actions` rather than the keyboard, so a controller,
+// or a finger on the web build (see touch.rs), works everywhere a key does.
+// Keys are looked up in `KeyBindings`, which the player can change and
+// which are saved with their progress. On screen prompts show the glyphs
+// for whichever was used last.
+
+use bevy::app::AppExit;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+
+use crate::GameState;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    // quits a level, or leaves the level select for the title
+    Back,
+    Undo,
+    Redo,
+    SwitchBranch,
+    Reset,
+    // opens the controls screen from the title
+    Controls,
+    // saves a replay of the level so far
+    SaveReplay,
+    Quit,
+}
+
+// in the order the controls screen lists them
+pub(crate) const ACTIONS: [(Action, &str); 13] = [
+    (Action::Up, "UP"),
+    (Action::Down, "DOWN"),
+    (Action::Left, "LEFT"),
+    (Action::Right, "RIGHT"),
+    (Action::Confirm, "CONFIRM"),
+    (Action::Back, "BACK"),
+    (Action::Undo, "UNDO"),
+    (Action::Redo, "REDO"),
+    (Action::SwitchBranch, "SWITCH BRANCH"),
+    (Action::Reset, "RESET"),
+    (Action::Controls, "CONTROLS"),
+    (Action::SaveReplay, "SAVE REPLAY"),
+    (Action::Quit, "QUIT"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Device {
+    #[default]
+    Keyboard,
+    Gamepad,
+    // a finger or the mouse
+    Touch,
+}
+
+const DEFAULT_KEYS: [(KeyCode, Action); 17] = [
+    (KeyCode::W, Action::Up),
+    (KeyCode::Up, Action::Up),
+    (KeyCode::S, Action::Down),
+    (KeyCode::Down, Action::Down),
+    (KeyCode::A, Action::Left),
+    (KeyCode::Left, Action::Left),
+    (KeyCode::D, Action::Right),
+    (KeyCode::Right, Action::Right),
+    (KeyCode::Return, Action::Confirm),
+    (KeyCode::Q, Action::Back),
+    (KeyCode::Z, Action::Undo),
+    (KeyCode::Y, Action::Redo),
+    (KeyCode::Tab, Action::SwitchBranch),
+    (KeyCode::R, Action::Reset),
+    (KeyCode::C, Action::Controls),
+    (KeyCode::F5, Action::SaveReplay),
+    (KeyCode::Escape, Action::Quit),
+];
+
+// laid out like an xbox pad
+const BUTTONS: [(GamepadButtonType, Action); 13] = [
+    (GamepadButtonType::DPadUp, Action::Up),
+    (GamepadButtonType::DPadDown, Action::Down),
+    (GamepadButtonType::DPadLeft, Action::Left),
+    (GamepadButtonType::DPadRight, Action::Right),
+    (GamepadButtonType::South, Action::Confirm),
+    (GamepadButtonType::Start, Action::Confirm),
+    (GamepadButtonType::East, Action::Back),
+    (GamepadButtonType::West, Action::Undo),
+    (GamepadButtonType::RightTrigger, Action::Redo),
+    (GamepadButtonType::LeftTrigger, Action::SwitchBranch),
+    (GamepadButtonType::North, Action::Reset),
+    (GamepadButtonType::Select, Action::Controls),
+    (GamepadButtonType::RightThumb, Action::SaveReplay),
+];
+
+// how far a stick has to be pushed to count as a press
+const STICK_THRESHOLD: f32 = 0.5;
+
+// each key does one thing; an action can have several keys, the first of
+// which is shown in prompts
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct KeyBindings(Vec<(KeyCode, Action)>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings(DEFAULT_KEYS.to_vec())
+    }
+}
+
+impl KeyBindings {
+    pub(crate) fn keys(&self, action: Action) -> impl Iterator<Item = KeyCode> + '_ {
+        self.0
+            .iter()
+            .filter(move |(_, bound)| *bound == action)
+            .map(|(key, _)| *key)
+    }
+
+    // `key` replaces the action's first key, and stops doing anything else;
+    // if it was all another action had, the two actions swap keys instead,
+    // so back and confirm can't be left without one
+    pub(crate) fn bind(&mut self, action: Action, key: KeyCode) {
+        let replaced = self.keys(action).next();
+        let stranded = self
+            .0
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, other)| *other)
+            .filter(|other| *other != action && self.keys(*other).count() == 1);
+        if stranded.is_some() && replaced.is_none() {
+            return;
+        }
+
+        self.0.retain(|(bound, _)| *bound != key);
+        match self.0.iter().position(|(_, bound)| *bound == action) {
+            Some(first) => self.0[first].0 = key,
+            None => self.0.push((key, action)),
+        }
+        if let (Some(other), Some(replaced)) = (stranded, replaced) {
+            self.0.push((replaced, other));
+        }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub(crate) struct ActionsLabel;
+
+// what was pressed this frame, and on what
+#[derive(Default)]
+pub(crate) struct Actions {
+    pressed: HashSet<Action>,
+    pub(crate) device: Device,
+}
+
+impl Actions {
+    pub(crate) fn just_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub(crate) fn press(&mut self, action: Action, device: Device) {
+        self.pressed.insert(action);
+        self.device = device;
+    }
+
+    // so a press that changes state isn't seen again by the next state's systems
+    pub(crate) fn clear(&mut self) {
+        self.pressed.clear();
+    }
+}
+
+// text that names the button for an action, e.g. "{} to start"
+pub(crate) struct Prompt {
+    pub(crate) template: &'static str,
+    pub(crate) action: Action,
+}
+
+// empty when the device has no way to do it
+pub(crate) fn glyph(action: Action, device: Device, bindings: &KeyBindings) -> String {
+    let button = match action {
+        Action::Up => "UP",
+        Action::Down => "DOWN",
+        Action::Left => "LEFT",
+        Action::Right => "RIGHT",
+        Action::Confirm => "A",
+        Action::Back => "B",
+        Action::Undo => "X",
+        Action::Redo => "RB",
+        Action::SwitchBranch => "LB",
+        Action::Reset => "Y",
+        Action::Controls => "SELECT",
+        Action::SaveReplay => "RS",
+        // only the keyboard quits
+        Action::Quit => "-",
+    };
+
+    match device {
+        Device::Keyboard => match bindings.keys(action).next() {
+            Some(key) => key_name(key),
+            None => "-".to_string(),
+        },
+        Device::Gamepad => button.to_string(),
+        // named as on the on-screen buttons
+        Device::Touch => match action {
+            Action::Up | Action::Down | Action::Left | Action::Right => "SWIPE",
+            Action::Confirm => "TAP",
+            Action::Back => "BACK",
+            Action::Undo => "UNDO",
+            Action::Reset => "RESET",
+            _ => "",
+        }
+        .to_string(),
+    }
+}
+
+pub(crate) fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Return => "ENTER".to_string(),
+        KeyCode::Escape => "ESC".to_string(),
+        KeyCode::Back => "BACKSPACE".to_string(),
+        key => {
+            let name = format!("{:?}", key).to_uppercase();
+            // the number keys are KEY1 and so on
+            name.trim_start_matches("KEY").to_string()
+        }
+    }
+}
+
+// the game and the editor's playtests both need these; only add them once
+pub(crate) fn add_controls(app: &mut AppBuilder) {
+    if app.world().get_resource::<Actions>().is_some() {
+        return;
+    }
+    app.init_resource::<Actions>()
+        .init_resource::<KeyBindings>()
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            read_actions.system().label(ActionsLabel).after(InputSystem),
+        )
+        .add_system(update_prompts.system());
+}
+
+fn read_actions(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    mut actions: ResMut<Actions>,
+    mut gamepads: Local<HashSet<Gamepad>>,
+    // the direction each gamepad's stick was held in last frame
+    mut sticks: Local<HashMap<Gamepad, Action>>,
+) {
+    actions.pressed.clear();
+
+    let shift = keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+    for (key, action) in bindings.0.iter() {
+        if keyboard_input.just_pressed(*key) {
+            // shift+z redoes too
+            let action = match action {
+                Action::Undo if shift => Action::Redo,
+                action => *action,
+            };
+            actions.pressed.insert(action);
+            actions.device = Device::Keyboard;
+        }
+    }
+
+    for GamepadEvent(gamepad, event) in gamepad_events.iter() {
+        match event {
+            GamepadEventType::Connected => {
+                gamepads.insert(*gamepad);
+            }
+            GamepadEventType::Disconnected => {
+                gamepads.remove(gamepad);
+                sticks.remove(gamepad);
+            }
+            _ => {}
+        }
+    }
+
+    for gamepad in gamepads.iter() {
+        for (button, action) in BUTTONS.iter() {
+            if gamepad_input.just_pressed(GamepadButton(*gamepad, *button)) {
+                actions.pressed.insert(*action);
+                actions.device = Device::Gamepad;
+            }
+        }
+
+        // some pads report the d-pad as an axis rather than buttons
+        let axis = |axis_type| axes.get(GamepadAxis(*gamepad, axis_type)).unwrap_or(0.);
+        let x = axis(GamepadAxisType::LeftStickX) + axis(GamepadAxisType::DPadX);
+        let y = axis(GamepadAxisType::LeftStickY) + axis(GamepadAxisType::DPadY);
+        let held = if x.abs() < STICK_THRESHOLD && y.abs() < STICK_THRESHOLD {
+            None
+        } else if x.abs() > y.abs() {
+            Some(if x > 0. { Action::Right } else { Action::Left })
+        } else {
+            Some(if y > 0. { Action::Up } else { Action::Down })
+        };
+
+        // a stick presses once each time it's pushed a new way
+        match held {
+            Some(action) if sticks.get(gamepad) != Some(&action) => {
+                sticks.insert(*gamepad, action);
+                actions.pressed.insert(action);
+                actions.device = Device::Gamepad;
+            }
+            Some(_) => {}
+            None => {
+                sticks.remove(gamepad);
+            }
+        }
+    }
+}
+
+fn update_prompts(
+    actions: Res<Actions>,
+    bindings: Res<KeyBindings>,
+    mut prompts: Query<(&Prompt, &mut Text)>,
+) {
+    for (prompt, mut text) in prompts.iter_mut() {
+        let mut name = glyph(prompt.action, actions.device, &bindings);
+        // lowercase prompts get lowercase glyphs
+        if !prompt.template.chars().any(char::is_uppercase) {
+            name = name.to_lowercase();
+        }
+
+        let value = if name.is_empty() {
+            String::new()
+        } else {
+            prompt.template.replace("{}", &name)
+        };
+        if let Some(section) = text.sections.first_mut() {
+            if section.value != value {
+                section.value = value;
+            }
+        }
+    }
+}
+
+// the controls screen takes any key, Esc included, to rebind it
+pub(crate) fn quit_game(
+    actions: Res<Actions>,
+    state: Res<State<GameState>>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    if actions.just_pressed(Action::Quit) && *state.current() != GameState::Controls {
+        app_exit.send(AppExit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, KeyBindings};
+    use bevy::prelude::KeyCode;
+
+    #[test]
+    fn rebinding_a_key_moves_it_between_actions() {
+        // azerty players move up with Z, which was undo
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::Up, KeyCode::Z);
+
+        let up: Vec<KeyCode> = bindings.keys(Action::Up).collect();
+        assert_eq!(up, vec![KeyCode::Z, KeyCode::Up]);
+        assert_eq!(
+            bindings.keys(Action::Undo).collect::<Vec<_>>(),
+            vec![KeyCode::W]
+        );
+
+        bindings.bind(Action::Undo, KeyCode::U);
+        assert_eq!(
+            bindings.keys(Action::Undo).collect::<Vec<_>>(),
+            vec![KeyCode::U]
+        );
+        assert_eq!(bindings.keys(Action::Up).count(), 2);
+        assert!(bindings.0.iter().all(|(key, _)| *key != KeyCode::W));
+    }
+
+    #[test]
+    fn no_action_loses_its_last_key() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::Up, KeyCode::Q);
+        assert_eq!(
+            bindings.keys(Action::Back).collect::<Vec<_>>(),
+            vec![KeyCode::W]
+        );
+
+        // with nothing to swap back, the key stays where it was
+        bindings.0.retain(|(_, bound)| *bound != Action::Reset);
+        bindings.bind(Action::Reset, KeyCode::W);
+        assert_eq!(bindings.keys(Action::Reset).next(), None);
+        assert_eq!(
+            bindings.keys(Action::Back).collect::<Vec<_>>(),
+            vec![KeyCode::W]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+// Undo and redo for the level editor.
+//
+// Edits are recorded as the tiles they changed rather than snapshots of the
+// level, so undoing only has to touch the locations involved. Holding a key
+// and dragging paints a stroke, which undoes as one edit.
+
+use crate::GridLocation;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Tile {
+    Ground,
+    // keeps the segment's place in the snake
+    Snake(u32),
+    Food,
+    Poison,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Change {
+    pub(crate) location: GridLocation,
+    pub(crate) before: Option<Tile>,
+    pub(crate) after: Option<Tile>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct EditHistory {
+    done: Vec<Vec<Change>>,
+    undone: Vec<Vec<Change>>,
+    stroke: Vec<Change>,
+}
+
+impl EditHistory {
+    pub(crate) fn record(&mut self, change: Change) {
+        // a stroke that crosses a tile twice still undoes to what was there first
+        match self
+            .stroke
+            .iter_mut()
+            .find(|earlier| earlier.location == change.location)
+        {
+            Some(earlier) => earlier.after = change.after,
+            None => self.stroke.push(change),
+        }
+    }
+
+    pub(crate) fn finish_stroke(&mut self) {
+        if self.stroke.is_empty() {
+            return;
+        }
+        self.done.push(std::mem::take(&mut self.stroke));
+        self.undone.clear();
+    }
+
+    // the tiles to put back, in the order to put them
+    pub(crate) fn undo(&mut self) -> Option<Vec<(GridLocation, Option<Tile>)>> {
+        self.finish_stroke();
+        let edit = self.done.pop()?;
+        let tiles = edit
+            .iter()
+            .rev()
+            .map(|change| (change.location.clone(), change.before.clone()))
+            .collect();
+        self.undone.push(edit);
+        Some(tiles)
+    }
+
+    pub(crate) fn redo(&mut self) -> Option<Vec<(GridLocation, Option<Tile>)>> {
+        self.finish_stroke();
+        let edit = self.undone.pop()?;
+        let tiles = edit
+            .iter()
+            .map(|change| (change.location.clone(), change.after.clone()))
+            .collect();
+        self.done.push(edit);
+        Some(tiles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Change, EditHistory, Tile};
+    use crate::GridLocation;
+
+    fn paint(history: &mut EditHistory, x: i32, before: Option<Tile>, after: Option<Tile>) {
+        history.record(Change {
+            location: GridLocation { x, y: 0 },
+            before,
+            after,
+        });
+    }
+
+    #[test]
+    fn undoes_a_stroke_at_a_time() {
+        let mut history = EditHistory::default();
+        paint(&mut history, 0, None, Some(Tile::Ground));
+        paint(&mut history, 1, Some(Tile::Food), Some(Tile::Ground));
+        history.finish_stroke();
+        paint(&mut history, 0, Some(Tile::Ground), None);
+        paint(&mut history, 0, None, Some(Tile::Poison));
+
+        assert_eq!(
+            history.undo(),
+            Some(vec![(GridLocation { x: 0, y: 0 }, Some(Tile::Ground))])
+        );
+        assert_eq!(
+            history.undo(),
+            Some(vec![
+                (GridLocation { x: 1, y: 0 }, Some(Tile::Food)),
+                (GridLocation { x: 0, y: 0 }, None),
+            ])
+        );
+        assert_eq!(history.undo(), None);
+
+        assert_eq!(
+            history.redo(),
+            Some(vec![
+                (GridLocation { x: 0, y: 0 }, Some(Tile::Ground)),
+                (GridLocation { x: 1, y: 0 }, Some(Tile::Ground)),
+            ])
+        );
+
+        // a new edit drops what was left to redo
+        paint(&mut history, 2, None, Some(Tile::Snake(0)));
+        history.finish_stroke();
+        assert_eq!(history.redo(), None);
+    }
+}
@@ -0,0 +1,190 @@
+// Shapes and the clipboard for the level editor's tools.
+//
+// These only deal in grid locations and tiles; the editor writes what they
+// return through to MyWorld a tile at a time, so every tool can be undone.
+
+use crate::edit_history::Tile;
+use crate::GridLocation;
+
+// every cell of the rectangle with corners `a` and `b`, row by row
+pub(crate) fn rect(a: &GridLocation, b: &GridLocation) -> Vec<GridLocation> {
+    let mut cells = vec![];
+    for y in a.y.min(b.y)..=a.y.max(b.y) {
+        for x in a.x.min(b.x)..=a.x.max(b.x) {
+            cells.push(GridLocation { x, y });
+        }
+    }
+    cells
+}
+
+// the cells of a straight line from `a` to `b`, in order; each shares an
+// edge with the last, so a snake painted along it stays in one piece
+pub(crate) fn line(a: &GridLocation, b: &GridLocation) -> Vec<GridLocation> {
+    let (dx, dy) = ((b.x - a.x).abs(), (b.y - a.y).abs());
+    let (step_x, step_y) = ((b.x - a.x).signum(), (b.y - a.y).signum());
+
+    let mut cells = vec![a.clone()];
+    let mut at = a.clone();
+    let (mut x, mut y) = (0, 0);
+    while x < dx || y < dy {
+        // step along whichever axis is further behind the true line
+        if (1 + 2 * x) * dy < (1 + 2 * y) * dx {
+            at.x += step_x;
+            x += 1;
+        } else {
+            at.y += step_y;
+            y += 1;
+        }
+        cells.push(at.clone());
+    }
+    cells
+}
+
+pub(crate) fn bottom_left(a: &GridLocation, b: &GridLocation) -> GridLocation {
+    GridLocation {
+        x: a.x.min(b.x),
+        y: a.y.min(b.y),
+    }
+}
+
+// numbers snake segments on from `next`, keeping their order, so pasting
+// a snake doesn't reuse the indices of the one already there
+pub(crate) fn renumber_snake(tiles: &mut [(GridLocation, Tile)], next: u32) {
+    let mut indices: Vec<u32> = tiles
+        .iter()
+        .filter_map(|(_, tile)| match tile {
+            Tile::Snake(index) => Some(*index),
+            _ => None,
+        })
+        .collect();
+    indices.sort_unstable();
+
+    for (_, tile) in tiles.iter_mut() {
+        if let Tile::Snake(index) = tile {
+            let rank = indices.iter().position(|i| i == index).unwrap_or(0);
+            *index = next + rank as u32;
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Clipboard {
+    // relative to the bottom left corner of what was copied
+    tiles: Vec<(GridLocation, Tile)>,
+    width: i32,
+    height: i32,
+}
+
+impl Clipboard {
+    // the tiles inside the rectangle with corners `a` and `b`
+    pub(crate) fn copy(
+        a: &GridLocation,
+        b: &GridLocation,
+        tiles: impl IntoIterator<Item = (GridLocation, Tile)>,
+    ) -> Clipboard {
+        let corner = bottom_left(a, b);
+        let (width, height) = ((a.x - b.x).abs() + 1, (a.y - b.y).abs() + 1);
+
+        let tiles = tiles
+            .into_iter()
+            .map(|(location, tile)| {
+                (
+                    GridLocation {
+                        x: location.x - corner.x,
+                        y: location.y - corner.y,
+                    },
+                    tile,
+                )
+            })
+            .filter(|(offset, _)| (0..width).contains(&offset.x) && (0..height).contains(&offset.y))
+            .collect();
+
+        Clipboard {
+            tiles,
+            width,
+            height,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.width == 0
+    }
+
+    pub(crate) fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    pub(crate) fn flip_horizontally(&mut self) {
+        for (offset, _) in self.tiles.iter_mut() {
+            offset.x = self.width - 1 - offset.x;
+        }
+    }
+
+    pub(crate) fn flip_vertically(&mut self) {
+        for (offset, _) in self.tiles.iter_mut() {
+            offset.y = self.height - 1 - offset.y;
+        }
+    }
+
+    // the copied tiles with their bottom left corner at `at`
+    pub(crate) fn paste(&self, at: &GridLocation) -> Vec<(GridLocation, Tile)> {
+        self.tiles
+            .iter()
+            .map(|(offset, tile)| (at.clone() + offset.clone(), tile.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{line, rect, renumber_snake, Clipboard};
+    use crate::edit_history::Tile;
+    use crate::GridLocation;
+
+    fn at(x: i32, y: i32) -> GridLocation {
+        GridLocation { x, y }
+    }
+
+    #[test]
+    fn fills_shapes_between_corners() {
+        assert_eq!(rect(&at(1, 1), &at(0, 0)).len(), 4);
+        assert_eq!(
+            line(&at(0, 0), &at(3, 1)),
+            vec![at(0, 0), at(1, 0), at(1, 1), at(2, 1), at(3, 1)]
+        );
+        assert_eq!(
+            line(&at(0, 2), &at(0, 0)),
+            vec![at(0, 2), at(0, 1), at(0, 0)]
+        );
+    }
+
+    #[test]
+    fn flips_and_pastes_a_copied_region() {
+        let tiles = vec![
+            (at(4, 4), Tile::Food),
+            (at(5, 5), Tile::Ground),
+            (at(9, 9), Tile::Poison),
+        ];
+        let mut clipboard = Clipboard::copy(&at(6, 5), &at(4, 4), tiles);
+        assert_eq!(clipboard.size(), (3, 2));
+
+        clipboard.flip_horizontally();
+        assert_eq!(
+            clipboard.paste(&at(0, 0)),
+            vec![(at(2, 0), Tile::Food), (at(1, 1), Tile::Ground)]
+        );
+
+        clipboard.flip_vertically();
+        assert_eq!(
+            clipboard.paste(&at(10, 0)),
+            vec![(at(12, 1), Tile::Food), (at(11, 0), Tile::Ground)]
+        );
+
+        let mut snake = vec![(at(0, 0), Tile::Snake(7)), (at(1, 0), Tile::Snake(3))];
+        renumber_snake(&mut snake, 2);
+        assert_eq!(
+            snake,
+            vec![(at(0, 0), Tile::Snake(3)), (at(1, 0), Tile::Snake(2))]
+        );
+    }
+}
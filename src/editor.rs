@@ -0,0 +1,1548 @@
+use bevy::{
+    app::AppExit,
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+    reflect::TypeRegistry,
+    window::ReceivedCharacter,
+};
+
+use std::collections::HashMap;
+use std::mem::discriminant;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use std::{
+    fs::{self, File},
+    io::Write,
+};
+
+use crate::edit_history::{Change, EditHistory, Tile};
+use crate::edit_tools::{self, Clipboard};
+use crate::game::{self, QuitTo};
+use crate::level::{self, SceneEntity};
+use crate::lint::{self, Problem};
+use crate::manifest::{self, Packs};
+use crate::solver::{self, Solution, SolverLimits, SolverReport};
+use crate::{
+    register_level_types, Food, GameState, GridLocation, Ground, MainCamera, MaybeSnakeAssets,
+    Poison, Snake, SnakeAssets, SnakeSegment, BOARD_RADIUS, GRID_HEIGHT, GRID_WIDTH,
+};
+
+struct Cursor;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+struct EditorLabel;
+
+// a tile drawn by the editor, as opposed to one still coming from the scene
+struct EditorTile;
+
+struct Coordinates;
+
+struct ChecksReport;
+
+// new levels are saved here until they're promoted
+const DRAFT_DIR: &str = "assets/scenes/tmp";
+const PROD_DIR: &str = "assets/scenes/prod";
+
+// what the name typed into the save dialog is for
+#[derive(Clone, Copy, PartialEq)]
+enum Naming {
+    SaveAs,
+    // saves, then copies the level into the prod pack
+    Promote,
+}
+
+struct SaveDialog {
+    naming: Naming,
+    name: String,
+    // Enter was pressed; promoting waits here for the solver to set a par
+    entered: bool,
+}
+
+struct DialogText;
+
+// lint results for the level as it stands, and the last solver run on it
+#[derive(Default)]
+struct Checks {
+    // what was checked, to tell when it needs checking again
+    tiles: Vec<(GridLocation, Tile)>,
+    entities: Vec<SceneEntity>,
+    problems: Vec<Problem>,
+    // filled in by the solver thread
+    solution: Option<Arc<Mutex<Option<SolverReport>>>>,
+}
+
+// small enough to answer in a few seconds without freezing the editor
+const EDITOR_SOLVER_LIMITS: SolverLimits = SolverLimits {
+    max_nodes: 500_000,
+    max_time: Some(Duration::from_secs(5)),
+};
+
+const OUTLINE: f32 = 4.0;
+
+// pixels a second at 1x
+const PAN_SPEED: f32 = 12.0 * GRID_WIDTH;
+// how much one wheel notch scales the view
+const ZOOM_STEP: f32 = 1.25;
+const MIN_SCALE: f32 = 0.25;
+const MAX_SCALE: f32 = 8.0;
+
+// what the left mouse button paints; the right one always erases
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+enum Brush {
+    #[default]
+    Ground,
+    Snake,
+    Food,
+    Poison,
+    Erase,
+}
+
+const BRUSHES: [(Brush, KeyCode, &str); 5] = [
+    (Brush::Ground, KeyCode::G, "G GROUND"),
+    (Brush::Snake, KeyCode::S, "S SNAKE"),
+    (Brush::Food, KeyCode::F, "F FOOD"),
+    (Brush::Poison, KeyCode::P, "P POISON"),
+    (Brush::Erase, KeyCode::D, "D ERASE"),
+];
+
+// how a drag paints with the brush
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+enum Tool {
+    #[default]
+    Pencil,
+    Line,
+    Rect,
+    // picks a region to copy, cut or flip
+    Select,
+}
+
+const TOOLS: [(Tool, KeyCode, &str); 4] = [
+    (Tool::Pencil, KeyCode::B, "B PENCIL"),
+    (Tool::Line, KeyCode::L, "L LINE"),
+    (Tool::Rect, KeyCode::R, "R RECT"),
+    (Tool::Select, KeyCode::M, "M SELECT"),
+];
+
+struct Palette;
+
+#[derive(Clone, Copy)]
+enum PaletteButton {
+    Brush(Brush),
+    Tool(Tool),
+}
+
+// a line, rectangle or selection being dragged out
+struct Drag {
+    tool: Tool,
+    button: MouseButton,
+    start: GridLocation,
+}
+
+// corners of the selected region
+#[derive(Default)]
+struct Selection(Option<(GridLocation, GridLocation)>);
+
+// cells highlighted over the level: the shape being dragged, or the selection
+#[derive(Default)]
+struct Overlay(Vec<GridLocation>);
+
+struct Highlight;
+
+// made once; tiles use the game's own sprites
+struct EditorMaterials {
+    cursor: Handle<ColorMaterial>,
+    wall: Handle<ColorMaterial>,
+    bounds: Handle<ColorMaterial>,
+    highlight: Handle<ColorMaterial>,
+    button: Handle<ColorMaterial>,
+    selected: Handle<ColorMaterial>,
+}
+
+impl FromWorld for EditorMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let wall = world
+            .get_resource::<AssetServer>()
+            .unwrap()
+            .load("sprites/drafts/wall.png");
+        let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
+        EditorMaterials {
+            cursor: materials.add(Color::rgb(164.0 / 255.0, 221.0 / 255.0, 219.0 / 255.0).into()),
+            wall: materials.add(wall.into()),
+            bounds: materials.add(Color::rgb(122.0 / 255.0, 54.0 / 255.0, 123.0 / 255.0).into()),
+            highlight: materials
+                .add(Color::rgba(164.0 / 255.0, 221.0 / 255.0, 219.0 / 255.0, 0.5).into()),
+            button: materials.add(Color::rgb(0.15, 0.15, 0.15).into()),
+            selected: materials.add(Color::rgb(0.35, 0.35, 0.6).into()),
+        }
+    }
+}
+
+struct MyWorld(World, TypeRegistry);
+
+// the scene being edited; `E` writes back to it, or asks where to save a new one
+#[derive(Default)]
+pub struct EditorFile(pub Option<PathBuf>);
+
+pub struct TaileaterEditorPlugin;
+
+impl Plugin for TaileaterEditorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        register_level_types(app);
+
+        app.add_state(GameState::Editor)
+            .insert_resource(QuitTo(GameState::Editor))
+            .insert_resource(MyWorld(World::new(), TypeRegistry::default()))
+            .init_resource::<EditorFile>()
+            .init_resource::<EditHistory>()
+            .init_resource::<Brush>()
+            .init_resource::<Tool>()
+            .init_resource::<Clipboard>()
+            .init_resource::<Selection>()
+            .init_resource::<Overlay>()
+            .init_resource::<Checks>()
+            .init_resource::<EditorMaterials>()
+            .add_startup_system(
+                (|world: &mut World| {
+                    let real_type_registry = world.get_resource::<TypeRegistry>().unwrap().clone();
+                    let mut my_world = world.get_resource_mut::<MyWorld>().unwrap();
+                    my_world.1 = real_type_registry;
+                })
+                .exclusive_system(),
+            )
+            .add_startup_system(
+                (|mut commands: Commands,
+                  materials: Res<EditorMaterials>,
+                  asset_server: Res<AssetServer>| {
+                    commands
+                        .spawn()
+                        .insert_bundle(OrthographicCameraBundle::new_2d())
+                        .insert(MainCamera);
+                    commands.spawn_bundle(UiCameraBundle::default());
+
+                    commands
+                        .spawn_bundle(TextBundle {
+                            text: Text {
+                                sections: vec![TextSection {
+                                    value: "".to_string(),
+                                    style: TextStyle {
+                                        font: asset_server.load("fonts/AsepriteFont.ttf"),
+                                        font_size: 40.0,
+                                        color: Color::rgb(0.5, 0.5, 1.0),
+                                    },
+                                }],
+                                ..Default::default()
+                            },
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                position: Rect {
+                                    top: Val::Px(5.0),
+                                    left: Val::Px(5.0),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .insert(Coordinates);
+
+                    commands
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                "",
+                                TextStyle {
+                                    font: asset_server.load("fonts/AsepriteFont.ttf"),
+                                    font_size: 24.0,
+                                    color: Color::WHITE,
+                                },
+                                Default::default(),
+                            ),
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                position: Rect {
+                                    bottom: Val::Px(5.0),
+                                    left: Val::Px(5.0),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .insert(ChecksReport);
+
+                    commands
+                        .spawn()
+                        .insert_bundle(SpriteBundle {
+                            sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+                            material: materials.cursor.clone(),
+                            ..Default::default()
+                        })
+                        .insert(GridLocation { x: 0, y: 0 })
+                        .insert(Cursor)
+                        .id();
+                })
+                .system(),
+            )
+            .add_startup_system(spawn_bounds.system())
+            .add_startup_system(open_scene.system())
+            .add_startup_system(game::load_assets.system())
+            .add_system_set(
+                SystemSet::on_update(GameState::Editor)
+                    .with_system(level_editor_cleanup.system())
+                    .with_system(move_camera.system())
+                    .with_system(editor.system().label(EditorLabel))
+                    .with_system(tools.system().after(EditorLabel))
+                    .with_system(palette.system())
+                    .with_system(draw_snake.system())
+                    .with_system(draw_overlay.system())
+                    .with_system(check_level.system())
+                    .with_system(save_level.system())
+                    .with_system(quit_editor.system())
+                    .with_system(start_playtest.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame).with_system(spawn_playtest.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Editor)
+                    .with_system(game::exit_ingame.system())
+                    .with_system(back_to_editing.system())
+                    .with_system(spawn_palette.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Naming).with_system(spawn_dialog.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Naming).with_system(name_level.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Naming).with_system(close_dialog.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Editor).with_system(
+                    (|mut query: Query<&mut Text, With<Coordinates>>,
+                      cursors: Query<&Transform, With<Cursor>>| {
+                        for x in cursors.iter() {
+                            for mut text in query.iter_mut() {
+                                text.sections[0].value = format!(
+                                    "({}, {})",
+                                    (x.translation.x / GRID_WIDTH) as i32,
+                                    (x.translation.y / GRID_HEIGHT) as i32
+                                );
+                            }
+                        }
+                    })
+                    .system(),
+                ),
+            );
+
+        game::add_level_systems(app);
+    }
+}
+
+// the board as the game draws it, with an outline so it shows up when zoomed out
+fn spawn_bounds(mut commands: Commands, materials: Res<EditorMaterials>) {
+    for x in -BOARD_RADIUS..=BOARD_RADIUS {
+        for y in -BOARD_RADIUS..=BOARD_RADIUS {
+            commands.spawn_bundle(SpriteBundle {
+                sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+                material: materials.wall.clone(),
+                transform: Transform::from_translation(Vec3::new(
+                    x as f32 * GRID_WIDTH,
+                    y as f32 * GRID_HEIGHT,
+                    -1.,
+                )),
+                ..Default::default()
+            });
+        }
+    }
+
+    let width = (2 * BOARD_RADIUS + 1) as f32 * GRID_WIDTH;
+    let height = (2 * BOARD_RADIUS + 1) as f32 * GRID_HEIGHT;
+    let edges = [
+        (0., height / 2., width + OUTLINE, OUTLINE),
+        (0., -height / 2., width + OUTLINE, OUTLINE),
+        (-width / 2., 0., OUTLINE, height + OUTLINE),
+        (width / 2., 0., OUTLINE, height + OUTLINE),
+    ];
+    for (x, y, edge_width, edge_height) in edges.iter() {
+        commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite::new(Vec2::new(*edge_width, *edge_height)),
+            material: materials.bounds.clone(),
+            transform: Transform::from_translation(Vec3::new(*x, *y, 3.)),
+            ..Default::default()
+        });
+    }
+}
+
+// middle drag or the arrow keys pan, the wheel or +/- zooms and Home resets
+fn move_camera(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    let mut pan = Vec2::ZERO;
+    let dragging = mouse_input.pressed(MouseButton::Middle);
+    for event in motion.iter() {
+        // the window's y runs down, the world's up
+        if dragging {
+            pan += Vec2::new(-event.delta.x, event.delta.y);
+        }
+    }
+
+    let mut direction = Vec2::ZERO;
+    for (key, step) in [
+        (KeyCode::Left, Vec2::new(-1., 0.)),
+        (KeyCode::Right, Vec2::new(1., 0.)),
+        (KeyCode::Down, Vec2::new(0., -1.)),
+        (KeyCode::Up, Vec2::new(0., 1.)),
+    ]
+    .iter()
+    {
+        if keyboard_input.pressed(*key) {
+            direction += *step;
+        }
+    }
+    pan += direction * PAN_SPEED * time.delta_seconds();
+
+    let mut zoom = 1.;
+    // sideways trackpad scrolling has no y, and signum would call it +1
+    for event in wheel.iter().filter(|event| event.y != 0.) {
+        zoom *= ZOOM_STEP.powf(-event.y.signum());
+    }
+    if keyboard_input.just_pressed(KeyCode::Equals) {
+        zoom /= ZOOM_STEP;
+    }
+    if keyboard_input.just_pressed(KeyCode::Minus) {
+        zoom *= ZOOM_STEP;
+    }
+
+    for mut camera in cameras.iter_mut() {
+        if keyboard_input.just_pressed(KeyCode::Home) {
+            *camera = Transform::from_translation(Vec3::new(0., 0., camera.translation.z));
+            continue;
+        }
+
+        // scale is world units per pixel, so pan the same distance on screen at any zoom
+        let scale = (camera.scale.x * zoom).clamp(MIN_SCALE, MAX_SCALE);
+        camera.translation += (pan * camera.scale.x).extend(0.);
+        camera.scale = Vec3::new(scale, scale, 1.);
+    }
+}
+
+// level_editor_cleanup copies the spawned scene into MyWorld
+fn open_scene(
+    editor_file: Res<EditorFile>,
+    asset_server: Res<AssetServer>,
+    mut scene_spawner: ResMut<SceneSpawner>,
+) {
+    if let Some(path) = &editor_file.0 {
+        let scene_handle: Handle<DynamicScene> = asset_server.load(path.as_path());
+        scene_spawner.spawn_dynamic(scene_handle);
+    }
+}
+
+fn level_editor_cleanup(
+    mut commands: Commands,
+    mut my_world: ResMut<MyWorld>,
+    snake_assets: Res<MaybeSnakeAssets>,
+
+    grounds: Query<(Entity, &GridLocation), (With<Ground>, Without<EditorTile>)>,
+    snakes: Query<
+        (Entity, &GridLocation, Option<&SnakeSegment>),
+        (With<Snake>, Without<EditorTile>),
+    >,
+    foods: Query<(Entity, &GridLocation), (With<Food>, Without<EditorTile>)>,
+    poisons: Query<(Entity, &GridLocation), (With<Poison>, Without<EditorTile>)>,
+) {
+    let snake_assets = match snake_assets.0.as_ref() {
+        Some(snake_assets) => snake_assets,
+        None => return,
+    };
+
+    // the scene's entities are swapped for editor tiles
+    let mut tiles = vec![];
+    for (e, grid_location) in grounds.iter() {
+        commands.entity(e).despawn();
+        tiles.push((grid_location.clone(), Tile::Ground));
+    }
+
+    // number the snake the way the game would order it, so older scenes are
+    // saved with an explicit order
+    let segments = snakes
+        .iter()
+        .map(|(e, grid_location, segment)| {
+            (
+                grid_location.clone(),
+                segment.map(|segment| segment.index),
+                (e, grid_location.clone()),
+            )
+        })
+        .collect();
+    for (index, (e, grid_location)) in level::snake_order(segments).into_iter().enumerate() {
+        commands.entity(e).despawn();
+        tiles.push((grid_location, Tile::Snake(index as u32)));
+    }
+
+    for (e, grid_location) in foods.iter() {
+        commands.entity(e).despawn();
+        tiles.push((grid_location.clone(), Tile::Food));
+    }
+
+    for (e, grid_location) in poisons.iter() {
+        commands.entity(e).despawn();
+        tiles.push((grid_location.clone(), Tile::Poison));
+    }
+
+    for (location, tile) in tiles {
+        spawn_tile(&mut commands, snake_assets, &mut my_world, &location, &tile);
+    }
+}
+
+fn editor(
+    mut commands: Commands,
+
+    wnds: Res<Windows>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    brush: Res<Brush>,
+    tool: Res<Tool>,
+    snake_assets: Res<MaybeSnakeAssets>,
+    mut my_world: ResMut<MyWorld>,
+    mut history: ResMut<EditHistory>,
+
+    camera: Query<&Transform, (With<MainCamera>, Without<Cursor>)>,
+    mut cursors: Query<
+        (&mut Transform, &mut GridLocation),
+        (With<Cursor>, Without<MainCamera>, Without<EditorTile>),
+    >,
+    grid_locations: Query<(&GridLocation, Entity), With<EditorTile>>,
+    interactions: Query<&Interaction>,
+) {
+    let snake_assets = match snake_assets.0.as_ref() {
+        Some(snake_assets) => snake_assets,
+        None => return,
+    };
+
+    let ctrl =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+    let reverted = if ctrl && keyboard_input.just_pressed(KeyCode::Z) {
+        history.undo()
+    } else if ctrl && keyboard_input.just_pressed(KeyCode::Y) {
+        history.redo()
+    } else {
+        None
+    };
+    if let Some(tiles) = reverted {
+        for (location, tile) in tiles {
+            set_tile(
+                &mut commands,
+                snake_assets,
+                &mut my_world,
+                &grid_locations,
+                &location,
+                tile.as_ref(),
+            );
+        }
+        return;
+    }
+
+    // one tile per frame; the right button erases
+    let painting = if *tool != Tool::Pencil || over_palette(&interactions) {
+        None
+    } else if mouse_input.pressed(MouseButton::Left) {
+        let next = next_segment(&mut my_world);
+        Some(brush_tile(*brush, next))
+    } else if mouse_input.pressed(MouseButton::Right) {
+        Some(None)
+    } else {
+        None
+    };
+    if painting.is_none() {
+        history.finish_stroke();
+    }
+
+    // get the primary window
+    let wnd = wnds.get_primary().unwrap();
+
+    // check if the cursor is in the primary window
+    if let Some(pos) = wnd.cursor_position() {
+        // get the size of the window
+        let size = Vec2::new(wnd.width() as f32, wnd.height() as f32);
+
+        // the default orthographic projection is in pixels from the center;
+        // just undo the translation
+        let p = pos - size / 2.0;
+
+        // assuming there is exactly one main camera entity, so this is OK
+        let camera_transform = camera.single().unwrap();
+
+        // apply the camera transform
+        let pos_wld = camera_transform.compute_matrix() * p.extend(0.0).extend(1.0);
+
+        let mouse_grid_location = GridLocation {
+            x: (pos_wld.x / GRID_WIDTH).round() as i32,
+            y: (pos_wld.y / GRID_HEIGHT).round() as i32,
+        };
+        let mouse_xform = Transform::from_translation(Vec3::new(
+            mouse_grid_location.x as f32 * GRID_WIDTH,
+            mouse_grid_location.y as f32 * GRID_HEIGHT,
+            0.,
+        ));
+
+        for (mut cursor, mut grid_location) in cursors.iter_mut() {
+            *cursor = mouse_xform;
+            *grid_location = mouse_grid_location.clone();
+        }
+
+        if let Some(after) = painting {
+            let before = tile_at(&mut my_world, &mouse_grid_location);
+
+            // painting over the same kind of tile would only renumber the snake
+            if before.as_ref().map(discriminant) != after.as_ref().map(discriminant) {
+                set_tile(
+                    &mut commands,
+                    snake_assets,
+                    &mut my_world,
+                    &grid_locations,
+                    &mouse_grid_location,
+                    after.as_ref(),
+                );
+                history.record(Change {
+                    location: mouse_grid_location.clone(),
+                    before,
+                    after,
+                });
+            }
+        }
+    }
+}
+
+// line and rectangle fills, and the clipboard; each is one edit to undo
+fn tools(
+    mut commands: Commands,
+
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    brush: Res<Brush>,
+    tool: Res<Tool>,
+    snake_assets: Res<MaybeSnakeAssets>,
+    mut my_world: ResMut<MyWorld>,
+    mut history: ResMut<EditHistory>,
+    mut clipboard: ResMut<Clipboard>,
+    mut selection: ResMut<Selection>,
+    mut overlay: ResMut<Overlay>,
+    mut drag: Local<Option<Drag>>,
+
+    cursors: Query<&GridLocation, With<Cursor>>,
+    grid_locations: Query<(&GridLocation, Entity), With<EditorTile>>,
+    interactions: Query<&Interaction>,
+) {
+    let snake_assets = match snake_assets.0.as_ref() {
+        Some(snake_assets) => snake_assets,
+        None => return,
+    };
+    let cursor = match cursors.single() {
+        Ok(cursor) => cursor.clone(),
+        Err(_) => return,
+    };
+
+    let ctrl =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+    let mut tiles = None;
+
+    if ctrl && (keyboard_input.just_pressed(KeyCode::C) || keyboard_input.just_pressed(KeyCode::X))
+    {
+        if let Some((a, b)) = &selection.0 {
+            *clipboard = Clipboard::copy(a, b, all_tiles(&mut my_world));
+            if keyboard_input.just_pressed(KeyCode::X) {
+                tiles = Some(
+                    edit_tools::rect(a, b)
+                        .into_iter()
+                        .map(|location| (location, None))
+                        .collect(),
+                );
+            }
+        }
+    } else if ctrl && keyboard_input.just_pressed(KeyCode::V) && !clipboard.is_empty() {
+        // pastes with its bottom left corner under the cursor, then selects it
+        let mut pasted = clipboard.paste(&cursor);
+        edit_tools::renumber_snake(&mut pasted, next_segment(&mut my_world));
+        tiles = Some(
+            pasted
+                .into_iter()
+                .map(|(location, tile)| (location, Some(tile)))
+                .collect(),
+        );
+
+        let (width, height) = clipboard.size();
+        let corner = GridLocation {
+            x: cursor.x + width - 1,
+            y: cursor.y + height - 1,
+        };
+        selection.0 = Some((cursor.clone(), corner));
+    } else if !ctrl
+        && (keyboard_input.just_pressed(KeyCode::H) || keyboard_input.just_pressed(KeyCode::V))
+    {
+        // flips the selection where it is
+        if let Some((a, b)) = &selection.0 {
+            let mut flipped = Clipboard::copy(a, b, all_tiles(&mut my_world));
+            if keyboard_input.just_pressed(KeyCode::H) {
+                flipped.flip_horizontally();
+            } else {
+                flipped.flip_vertically();
+            }
+
+            let mut after: HashMap<_, _> = flipped
+                .paste(&edit_tools::bottom_left(a, b))
+                .into_iter()
+                .collect();
+            tiles = Some(
+                edit_tools::rect(a, b)
+                    .into_iter()
+                    .map(|location| {
+                        let tile = after.remove(&location);
+                        (location, tile)
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    if drag.is_none() && *tool != Tool::Pencil && !over_palette(&interactions) {
+        for button in [MouseButton::Left, MouseButton::Right].iter() {
+            if mouse_input.just_pressed(*button) {
+                *drag = Some(Drag {
+                    tool: *tool,
+                    button: *button,
+                    start: cursor.clone(),
+                });
+            }
+        }
+    }
+
+    let mut highlighted = None;
+    if let Some(current) = drag.take() {
+        let cells = match current.tool {
+            Tool::Line => edit_tools::line(&current.start, &cursor),
+            _ => edit_tools::rect(&current.start, &cursor),
+        };
+
+        if mouse_input.pressed(current.button) {
+            highlighted = Some(cells);
+            *drag = Some(current);
+        } else {
+            match (current.tool, current.button) {
+                (Tool::Select, MouseButton::Left) => selection.0 = Some((current.start, cursor)),
+                // right clicking drops the selection
+                (Tool::Select, _) => selection.0 = None,
+                (_, MouseButton::Left) => {
+                    let next = next_segment(&mut my_world);
+                    tiles = Some(
+                        cells
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, location)| (location, brush_tile(*brush, next + i as u32)))
+                            .collect(),
+                    );
+                }
+                _ => tiles = Some(cells.into_iter().map(|location| (location, None)).collect()),
+            }
+        }
+    }
+
+    let highlighted = highlighted.unwrap_or_else(|| {
+        selection
+            .0
+            .as_ref()
+            .map(|(a, b)| edit_tools::rect(a, b))
+            .unwrap_or_default()
+    });
+    if overlay.0 != highlighted {
+        overlay.0 = highlighted;
+    }
+
+    if let Some(tiles) = tiles {
+        history.finish_stroke();
+        for (location, after) in tiles {
+            let before = tile_at(&mut my_world, &location);
+            if before != after {
+                set_tile(
+                    &mut commands,
+                    snake_assets,
+                    &mut my_world,
+                    &grid_locations,
+                    &location,
+                    after.as_ref(),
+                );
+                history.record(Change {
+                    location,
+                    before,
+                    after,
+                });
+            }
+        }
+        history.finish_stroke();
+    }
+}
+
+// the palette sits over the level; clicking it doesn't paint
+fn over_palette(interactions: &Query<&Interaction>) -> bool {
+    interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+}
+
+// segments are numbered in the order they are painted, head first
+fn next_segment(my_world: &mut MyWorld) -> u32 {
+    my_world
+        .0
+        .query::<&SnakeSegment>()
+        .iter(&my_world.0)
+        .map(|segment| segment.index + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+fn brush_tile(brush: Brush, next_segment: u32) -> Option<Tile> {
+    match brush {
+        Brush::Ground => Some(Tile::Ground),
+        Brush::Snake => Some(Tile::Snake(next_segment)),
+        Brush::Food => Some(Tile::Food),
+        Brush::Poison => Some(Tile::Poison),
+        Brush::Erase => None,
+    }
+}
+
+fn draw_overlay(
+    mut commands: Commands,
+    overlay: Res<Overlay>,
+    materials: Res<EditorMaterials>,
+    highlights: Query<Entity, With<Highlight>>,
+) {
+    if !overlay.is_changed() {
+        return;
+    }
+
+    for e in highlights.iter() {
+        commands.entity(e).despawn();
+    }
+    for location in overlay.0.iter() {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+                material: materials.highlight.clone(),
+                transform: Transform::from_translation(Vec3::new(
+                    location.x as f32 * GRID_WIDTH,
+                    location.y as f32 * GRID_HEIGHT,
+                    2.,
+                )),
+                ..Default::default()
+            })
+            .insert(Highlight);
+    }
+}
+
+// lints the level whenever it changes, and solves it on F5
+fn check_level(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut my_world: ResMut<MyWorld>,
+    mut checks: ResMut<Checks>,
+    mut reports: Query<&mut Text, With<ChecksReport>>,
+) {
+    // checked as it would be exported
+    let tiles = all_tiles(&mut my_world);
+    if tiles != checks.tiles {
+        let scene = DynamicScene::from_world(&my_world.0, &my_world.1);
+        checks.entities = scene
+            .serialize_ron(&my_world.1)
+            .ok()
+            .and_then(|data| level::parse_scene(&data).ok())
+            .unwrap_or_default();
+        checks.problems = lint::lint(&checks.entities);
+        checks.tiles = tiles;
+        checks.solution = None;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::F5) && checks.problems.is_empty() {
+        start_solving(&mut checks);
+    }
+
+    let mut lines: Vec<String> = checks
+        .problems
+        .iter()
+        .map(|problem| problem.to_string().to_uppercase())
+        .collect();
+    lines.push(match &checks.solution {
+        _ if !checks.problems.is_empty() => "FIX THESE TO SOLVE".to_string(),
+        None => "F5 TO SOLVE".to_string(),
+        Some(solution) => match &*solution.lock().unwrap() {
+            None => "SOLVING...".to_string(),
+            Some(report) => solver_summary(report),
+        },
+    });
+    let value = lines.join("\n");
+
+    let color = if checks.problems.is_empty() {
+        Color::WHITE
+    } else {
+        Color::rgb(1.0, 0.45, 0.45)
+    };
+    for mut text in reports.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+// solves the level off the main thread; `checks.solution` is filled in when
+// it's done
+fn start_solving(checks: &mut Checks) {
+    let level = level::Level::from_scene(&checks.entities);
+    let solution = Arc::new(Mutex::new(None));
+    checks.solution = Some(solution.clone());
+    thread::spawn(move || {
+        let report = solver::solve(&level, &EDITOR_SOLVER_LIMITS);
+        *solution.lock().unwrap() = Some(report);
+    });
+}
+
+fn solver_summary(report: &SolverReport) -> String {
+    match &report.solution {
+        Solution::Solved(moves) => format!("SOLVABLE IN {} MOVES", moves.len()),
+        Solution::Unsolvable => "NO SOLUTION".to_string(),
+        Solution::GaveUp => format!("NO SOLUTION FOUND IN {} STATES", report.explored),
+    }
+}
+
+// Enter plays the level as it stands; quitting it (Q) comes back here
+fn start_playtest(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    mut history: ResMut<EditHistory>,
+    mut selection: ResMut<Selection>,
+    mut overlay: ResMut<Overlay>,
+
+    tiles: Query<Entity, Or<(With<EditorTile>, With<Palette>, With<Highlight>)>>,
+    mut cursors: Query<&mut Visible, With<Cursor>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    // the game spawns its own copy of the level; MyWorld is left alone
+    for e in tiles.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    for mut visible in cursors.iter_mut() {
+        visible.is_visible = false;
+    }
+    history.finish_stroke();
+    selection.0 = None;
+    overlay.0.clear();
+
+    state.set(GameState::InGame).ok();
+}
+
+fn spawn_playtest(
+    my_world: Res<MyWorld>,
+    mut scenes: ResMut<Assets<DynamicScene>>,
+    mut scene_spawner: ResMut<SceneSpawner>,
+    mut bg_color: ResMut<ClearColor>,
+) {
+    let scene = DynamicScene::from_world(&my_world.0, &my_world.1);
+    scene_spawner.spawn_dynamic(scenes.add(scene));
+
+    *bg_color = ClearColor(Color::rgb(87. / 255., 114. / 255., 119. / 255.));
+}
+
+// redraw the level from MyWorld, which the playtest never touched
+fn back_to_editing(
+    mut commands: Commands,
+    snake_assets: Res<MaybeSnakeAssets>,
+    mut my_world: ResMut<MyWorld>,
+    mut bg_color: ResMut<ClearColor>,
+
+    mut cursors: Query<&mut Visible, With<Cursor>>,
+) {
+    let snake_assets = snake_assets.0.as_ref().expect("fully loaded");
+
+    for (location, tile) in all_tiles(&mut my_world) {
+        spawn_sprite(&mut commands, snake_assets, &location, &tile);
+    }
+
+    for mut visible in cursors.iter_mut() {
+        visible.is_visible = true;
+    }
+    *bg_color = ClearColor::default();
+}
+
+fn tile_at(my_world: &mut MyWorld, location: &GridLocation) -> Option<Tile> {
+    tiles_where(my_world, |grid_location| grid_location == location)
+        .pop()
+        .map(|(_, tile)| tile)
+}
+
+fn all_tiles(my_world: &mut MyWorld) -> Vec<(GridLocation, Tile)> {
+    tiles_where(my_world, |_| true)
+}
+
+fn tiles_where(
+    my_world: &mut MyWorld,
+    mut keep: impl FnMut(&GridLocation) -> bool,
+) -> Vec<(GridLocation, Tile)> {
+    let mut q = my_world.0.query::<(
+        &GridLocation,
+        Option<&Ground>,
+        Option<&SnakeSegment>,
+        Option<&Food>,
+        Option<&Poison>,
+    )>();
+
+    q.iter(&my_world.0)
+        .filter(|(grid_location, ..)| keep(grid_location))
+        .filter_map(|(grid_location, ground, segment, food, poison)| {
+            let tile = if ground.is_some() {
+                Tile::Ground
+            } else if let Some(segment) = segment {
+                Tile::Snake(segment.index)
+            } else if food.is_some() {
+                Tile::Food
+            } else if poison.is_some() {
+                Tile::Poison
+            } else {
+                return None;
+            };
+            Some((grid_location.clone(), tile))
+        })
+        .collect()
+}
+
+// replaces whatever is at `location`, in MyWorld and on screen
+fn set_tile(
+    commands: &mut Commands,
+    snake_assets: &SnakeAssets,
+    my_world: &mut MyWorld,
+    grid_locations: &Query<(&GridLocation, Entity), With<EditorTile>>,
+    location: &GridLocation,
+    tile: Option<&Tile>,
+) {
+    let mut q = my_world.0.query::<(&GridLocation, Entity)>();
+
+    let mut to_despawn = vec![];
+    for (grid_location, e) in q.iter(&my_world.0) {
+        if grid_location == location {
+            to_despawn.push(e);
+        }
+    }
+    for e in to_despawn {
+        my_world.0.despawn(e);
+    }
+
+    for (grid_location, e) in grid_locations.iter() {
+        if grid_location == location {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+
+    if let Some(tile) = tile {
+        spawn_tile(commands, snake_assets, my_world, location, tile);
+    }
+}
+
+fn spawn_tile(
+    commands: &mut Commands,
+    snake_assets: &SnakeAssets,
+    my_world: &mut MyWorld,
+    location: &GridLocation,
+    tile: &Tile,
+) {
+    spawn_sprite(commands, snake_assets, location, tile);
+
+    let mut entity = my_world.0.spawn();
+    entity.insert(location.clone());
+
+    match tile {
+        Tile::Ground => entity.insert(Ground),
+        Tile::Snake(index) => entity.insert(Snake).insert(SnakeSegment { index: *index }),
+        Tile::Food => entity.insert(Food),
+        Tile::Poison => entity.insert(Poison),
+    };
+}
+
+// snake tiles start as any body sprite; draw_snake picks the right one
+fn spawn_sprite(
+    commands: &mut Commands,
+    snake_assets: &SnakeAssets,
+    location: &GridLocation,
+    tile: &Tile,
+) {
+    let transform = Transform::from_translation(Vec3::new(
+        location.x as f32 * GRID_WIDTH,
+        location.y as f32 * GRID_HEIGHT,
+        0.,
+    ));
+
+    let mut sprite = commands.spawn();
+    sprite.insert(location.clone()).insert(EditorTile);
+
+    match tile {
+        Tile::Ground => sprite
+            .insert_bundle(tile_sprite(snake_assets.ground.clone(), transform))
+            .insert(Ground),
+        Tile::Snake(_) => sprite
+            .insert_bundle(SpriteSheetBundle {
+                texture_atlas: snake_assets.light_body.clone(),
+                transform,
+                ..Default::default()
+            })
+            .insert(Snake),
+        Tile::Food => sprite
+            .insert_bundle(tile_sprite(snake_assets.food.clone(), transform))
+            .insert(Food),
+        Tile::Poison => sprite
+            .insert_bundle(tile_sprite(snake_assets.poison.clone(), transform))
+            .insert(Poison),
+    };
+}
+
+fn tile_sprite(material: Handle<ColorMaterial>, transform: Transform) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+        material,
+        transform,
+        ..Default::default()
+    }
+}
+
+// snake tiles show the sprite the game would start each segment with
+fn draw_snake(
+    mut my_world: ResMut<MyWorld>,
+    snake_assets: Res<MaybeSnakeAssets>,
+
+    mut sprites: Query<
+        (
+            &GridLocation,
+            &mut Handle<TextureAtlas>,
+            &mut TextureAtlasSprite,
+            &mut Transform,
+        ),
+        With<EditorTile>,
+    >,
+) {
+    let snake_assets = match snake_assets.0.as_ref() {
+        Some(snake_assets) => snake_assets,
+        None => return,
+    };
+
+    let segments = my_world
+        .0
+        .query::<(&GridLocation, &SnakeSegment)>()
+        .iter(&my_world.0)
+        .map(|(grid_location, segment)| {
+            (
+                grid_location.clone(),
+                Some(segment.index),
+                grid_location.clone(),
+            )
+        })
+        .collect();
+    let locations = level::snake_order(segments);
+    let resting: HashMap<_, _> = locations
+        .iter()
+        .cloned()
+        .zip(game::resting_sprites(snake_assets, &locations))
+        .collect();
+
+    for (grid_location, mut atlas, mut sprite, mut transform) in sprites.iter_mut() {
+        if let Some((resting_atlas, index, z)) = resting.get(grid_location) {
+            if *atlas != *resting_atlas {
+                *atlas = resting_atlas.clone();
+            }
+            if sprite.index != *index {
+                sprite.index = *index;
+            }
+            transform.translation.z = *z;
+        }
+    }
+}
+
+// pick what the left button paints, from the palette or with its key
+fn palette(
+    keyboard_input: Res<Input<KeyCode>>,
+    materials: Res<EditorMaterials>,
+    mut brush: ResMut<Brush>,
+    mut tool: ResMut<Tool>,
+
+    clicked: Query<(&Interaction, &PaletteButton), Changed<Interaction>>,
+    mut buttons: Query<(&PaletteButton, &mut Handle<ColorMaterial>)>,
+) {
+    let ctrl =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+    if !ctrl {
+        for (key_brush, key, _label) in BRUSHES.iter() {
+            if keyboard_input.just_pressed(*key) {
+                *brush = *key_brush;
+            }
+        }
+        for (key_tool, key, _label) in TOOLS.iter() {
+            if keyboard_input.just_pressed(*key) {
+                *tool = *key_tool;
+            }
+        }
+    }
+    for (interaction, button) in clicked.iter() {
+        if *interaction == Interaction::Clicked {
+            match button {
+                PaletteButton::Brush(button) => *brush = *button,
+                PaletteButton::Tool(button) => *tool = *button,
+            }
+        }
+    }
+
+    if brush.is_changed() || tool.is_changed() {
+        for (button, mut material) in buttons.iter_mut() {
+            *material = button_material(&materials, is_selected(*button, *brush, *tool));
+        }
+    }
+}
+
+fn is_selected(button: PaletteButton, brush: Brush, tool: Tool) -> bool {
+    match button {
+        PaletteButton::Brush(button) => button == brush,
+        PaletteButton::Tool(button) => button == tool,
+    }
+}
+
+fn spawn_palette(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<EditorMaterials>,
+    brush: Res<Brush>,
+    tool: Res<Tool>,
+) {
+    let font = asset_server.load("fonts/AsepriteFont.ttf");
+    let buttons = BRUSHES
+        .iter()
+        .map(|(brush, _key, label)| (PaletteButton::Brush(*brush), *label))
+        .chain(
+            TOOLS
+                .iter()
+                .map(|(tool, _key, label)| (PaletteButton::Tool(*tool), *label)),
+        );
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(5.0),
+                    right: Val::Px(5.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: Rect::all(Val::Px(4.0)),
+                ..Default::default()
+            },
+            material: materials.button.clone(),
+            ..Default::default()
+        })
+        // so clicks between the buttons don't paint either
+        .insert(Interaction::default())
+        .insert(Palette)
+        .with_children(|parent| {
+            for (button, label) in buttons {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(180.0), Val::Px(40.0)),
+                            margin: Rect::all(Val::Px(4.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        material: button_material(&materials, is_selected(button, *brush, *tool)),
+                        ..Default::default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                label,
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 24.0,
+                                    color: Color::WHITE,
+                                },
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        });
+                    });
+            }
+        });
+}
+
+fn button_material(materials: &EditorMaterials, selected: bool) -> Handle<ColorMaterial> {
+    if selected {
+        materials.selected.clone()
+    } else {
+        materials.button.clone()
+    }
+}
+
+// Esc quits, unless it's closing the save dialog
+fn quit_editor(keyboard_input: Res<Input<KeyCode>>, mut app_exit: EventWriter<AppExit>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        app_exit.send(AppExit);
+    }
+}
+
+// E saves over the open scene; Shift+E, or E with nothing open, asks for a
+// name first, and Ctrl+E names the level and promotes it to prod
+fn save_level(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    editor_file: Res<EditorFile>,
+    my_world: Res<MyWorld>,
+    checks: Res<Checks>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::E) {
+        return;
+    }
+    let ctrl =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+    let shift = keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+
+    let naming = match &editor_file.0 {
+        _ if ctrl => Naming::Promote,
+        Some(path) if !shift => {
+            save_scene(&my_world, path);
+            return;
+        }
+        _ => Naming::SaveAs,
+    };
+    if naming == Naming::Promote && !checks.problems.is_empty() {
+        eprintln!("fix the level's problems before promoting it");
+        return;
+    }
+
+    // start from the open scene's name, or the time for a new one
+    let name = editor_file
+        .0
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|file| {
+            file.to_string_lossy()
+                .trim_end_matches(".scn.ron")
+                .to_string()
+        })
+        .unwrap_or_else(|| chrono::Local::now().format("%Y%m%d_%H%M%S").to_string());
+
+    commands.insert_resource(SaveDialog {
+        naming,
+        name,
+        entered: false,
+    });
+    state.push(GameState::Naming).ok();
+}
+
+fn spawn_dialog(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut characters: ResMut<Events<ReceivedCharacter>>,
+) {
+    // the E that opened the dialog isn't part of the name
+    characters.clear();
+
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/AsepriteFont.ttf"),
+                    font_size: 40.0,
+                    color: Color::rgb(0.5, 0.5, 1.0),
+                },
+                Default::default(),
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(60.0),
+                    left: Val::Px(5.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(DialogText);
+}
+
+// types the level's name; Enter saves, Escape goes back to editing
+fn name_level(
+    mut state: ResMut<State<GameState>>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut dialog: ResMut<SaveDialog>,
+    mut editor_file: ResMut<EditorFile>,
+    my_world: Res<MyWorld>,
+    mut checks: ResMut<Checks>,
+
+    mut texts: Query<&mut Text, With<DialogText>>,
+) {
+    for character in characters.iter() {
+        if !dialog.entered
+            && (character.char.is_ascii_alphanumeric() || " _-".contains(character.char))
+        {
+            dialog.name.push(character.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) && !dialog.entered {
+        dialog.name.pop();
+    }
+
+    let file = manifest::scene_file_name(&dialog.name);
+    let prompt = match dialog.naming {
+        Naming::SaveAs => "SAVE AS",
+        Naming::Promote => "PROMOTE AS",
+    };
+    let hint = if dialog.entered {
+        "SOLVING FOR PAR, ESC TO CANCEL"
+    } else {
+        "ENTER TO SAVE, ESC TO CANCEL"
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = format!(
+            "{}: {}_\n{}\n{}",
+            prompt,
+            dialog.name.to_uppercase(),
+            file.as_deref().unwrap_or("TYPE A NAME"),
+            hint,
+        );
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        // or the editor would take it as quitting
+        keyboard_input.reset(KeyCode::Escape);
+        state.pop().ok();
+        return;
+    }
+    let file = match file {
+        Some(file) if dialog.entered || keyboard_input.just_pressed(KeyCode::Return) => file,
+        _ => return,
+    };
+    if !dialog.entered {
+        // Enter would start a playtest once the editor's running again
+        keyboard_input.reset(KeyCode::Return);
+        dialog.entered = true;
+
+        // a solve started with F5 will do
+        if dialog.naming == Naming::Promote && checks.solution.is_none() {
+            start_solving(&mut checks);
+        }
+    }
+
+    // every prod level ships with a par; wait for the solver to find it
+    let par = match dialog.naming {
+        Naming::Promote => {
+            let report = match checks
+                .solution
+                .as_ref()
+                .and_then(|solution| solution.lock().unwrap().clone())
+            {
+                Some(report) => report,
+                None => return,
+            };
+            match report.solution {
+                Solution::Solved(moves) => Some(moves.len() as u32),
+                _ => {
+                    eprintln!("couldn't solve the level to set its par; not promoting it");
+                    state.pop().ok();
+                    return;
+                }
+            }
+        }
+        Naming::SaveAs => None,
+    };
+
+    // saves go next to the open scene, but a promoted level's draft stays
+    // out of prod, which only holds the copy listed in its manifest
+    let dir = match (
+        dialog.naming,
+        editor_file.0.as_ref().and_then(|path| path.parent()),
+    ) {
+        (Naming::SaveAs, Some(dir)) => dir.to_path_buf(),
+        _ => PathBuf::from(DRAFT_DIR),
+    };
+    let path = dir.join(file);
+    if !save_scene(&my_world, &path) {
+        // let them try another name
+        dialog.entered = false;
+        return;
+    }
+    editor_file.0 = Some(path.clone());
+
+    if dialog.naming == Naming::Promote {
+        match manifest::promote(
+            &path,
+            Path::new(PROD_DIR),
+            dialog.name.to_uppercase(),
+            std::env::var("USER").unwrap_or_default(),
+            par,
+            &Packs::bundled(),
+        ) {
+            Ok(level) => println!(
+                "Promoted {} to {} as {:?}",
+                path.display(),
+                PROD_DIR,
+                level.id
+            ),
+            Err(why) => eprintln!("couldn't promote {}: {}", path.display(), why),
+        }
+    }
+    state.pop().ok();
+}
+
+fn close_dialog(mut commands: Commands, texts: Query<Entity, With<DialogText>>) {
+    for e in texts.iter() {
+        commands.entity(e).despawn();
+    }
+    commands.remove_resource::<SaveDialog>();
+}
+
+fn save_scene(my_world: &MyWorld, path: &Path) -> bool {
+    let scene = DynamicScene::from_world(&my_world.0, &my_world.1);
+    let data = scene.serialize_ron(&my_world.1).unwrap();
+    if let Some(dir) = path.parent() {
+        if let Err(why) = fs::create_dir_all(dir) {
+            eprintln!("couldn't create {}: {}", dir.display(), why);
+            return false;
+        }
+    }
+    // Open a file in write-only mode, returns `io::Result<File>`
+    match File::create(&path) {
+        Err(why) => {
+            eprintln!("couldn't save to {}: {}", path.display(), why);
+            false
+        }
+        Ok(mut file) => match file.write_all(data.as_bytes()) {
+            Err(why) => panic!("couldn't write to {}: {}", path.display(), why),
+            Ok(_) => {
+                println!("Successfully wrote to {}", path.display());
+                true
+            }
+        },
+    }
+}
+
+// update sprite based on each direction
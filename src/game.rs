@@ -0,0 +1,2011 @@
+use bevy::prelude::*;
+
+use crate::audio::add_audio_cues;
+use crate::controls::{add_controls, quit_game, Action, Actions};
+use crate::history::History;
+use crate::input_buffer::InputBuffer;
+use crate::level;
+use crate::manifest::{self, LevelsDir, Packs};
+use crate::playback::{Playback, ReplayPlugin};
+use crate::rebind::RebindPlugin;
+use crate::rules::{Board, Eaten, Poisoned, PuzzleState};
+use crate::save;
+use crate::stats::StatsPlugin;
+use crate::title::TitlePlugin;
+use crate::touch::TouchPlugin;
+use crate::tween::{MoveAnimation, Tween};
+use crate::{
+    register_level_types, rules, AcceptedInput, AudioCue, BufferLabel, BufferedInput, Direction,
+    Food, FoodLabel, GameHistory, GameState, GravityLabel, GridLocation, Ground, HistoryLabel,
+    InputLabel, LevelId, LocationQueue, MaybeSnakeAssets, Orientation, PlayerInput, Poison,
+    PoisonLabel, Selected, Snake, SnakeAssets, SnakeMovementLabel, SnakeParts, SnakeSegment,
+    Snapshot, SpriteLabel, TransformLabel, Transition, TransitionQueue, WinLabel, BOARD_RADIUS,
+    GRID_HEIGHT, GRID_WIDTH,
+};
+
+pub struct TaileaterGamePlugin;
+
+impl Plugin for TaileaterGamePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        register_level_types(app);
+
+        app.add_state(GameState::Szunami)
+            .add_plugin(TitlePlugin)
+            .add_plugin(RebindPlugin)
+            .add_plugin(TouchPlugin)
+            .add_system(quit_game.system())
+            .insert_resource(Packs::bundled())
+            .add_startup_system(load_custom_levels.system())
+            .insert_resource(Selected(GridLocation { x: 0, y: 0 }, LevelId(0)))
+            .insert_resource(QuitTo(GameState::LevelSelect))
+            .add_plugin(ReplayPlugin)
+            .add_plugin(StatsPlugin)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Szunami).with_system(save::load_progress.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Szunami).with_system(load_assets.system()),
+            )
+            // This is a little messy; need to handle quitting game + winning
+            .add_system_set(
+                SystemSet::on_enter(GameState::LevelSelect).with_system(exit_ingame.system()),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup.system()));
+
+        add_level_systems(app);
+    }
+}
+
+// the systems that play a level once its scene is spawning; the editor's
+// playtest shares them with the game
+pub(crate) fn add_level_systems(app: &mut AppBuilder) {
+    add_audio_cues(app);
+    add_controls(app);
+
+    app.insert_resource(MaybeSnakeAssets(None))
+        .insert_resource(SnakeParts(vec![]))
+        .insert_resource(GameHistory(None))
+        .init_resource::<MoveAnimation>()
+        .init_resource::<InputBuffer>()
+        .add_event::<PlayerInput>()
+        .add_event::<BufferedInput>()
+        .add_event::<AcceptedInput>()
+        .add_system_set(
+            SystemSet::on_enter(GameState::InGame)
+                .with_system(wall.system())
+                .with_system(clear_input_buffer.system()),
+        )
+        .add_system_set(SystemSet::on_update(GameState::InGame).with_system(cleanup.system()))
+        .add_system_set(SystemSet::on_update(GameState::InGame).with_system(quit_level.system()))
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(read_controls.system())
+                .label(InputLabel),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(buffer_inputs.system())
+                .label(BufferLabel)
+                .after(InputLabel),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(update_history.system())
+                .label(HistoryLabel)
+                .after(BufferLabel),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(food.system())
+                .label(FoodLabel)
+                .after(HistoryLabel),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(poison.system())
+                .label(PoisonLabel)
+                .after(FoodLabel),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(snake_movement.system())
+                .label(SnakeMovementLabel)
+                .after(PoisonLabel),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(sprite.system().label(SpriteLabel).after(SnakeMovementLabel)),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(gravity.system())
+                .label(GravityLabel)
+                .after(SnakeMovementLabel),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(gridlocation_to_transform.system())
+                .label(TransformLabel), // .after(GravityLabel),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(win.system())
+                .label(WinLabel)
+                .after(GravityLabel),
+        )
+        .add_system_set(SystemSet::on_enter(GameState::Win).with_system(enter_win.system()))
+        .add_system_set(SystemSet::on_update(GameState::Win).with_system(update_win.system()))
+        .add_system_set(SystemSet::on_update(GameState::Win).with_system(quit_level.system()));
+}
+
+// where quitting a level goes
+pub(crate) struct QuitTo(pub(crate) GameState);
+
+pub(crate) fn load_assets(
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut snake_assets: ResMut<MaybeSnakeAssets>,
+) {
+    dbg!("loading assets");
+    let light_body = asset_server.load("sprites/drafts/body_light.png");
+    let light_body = TextureAtlas::from_grid(light_body, Vec2::new(96.0, 96.0), 5, 36);
+    let light_body = texture_atlases.add(light_body);
+
+    let dark_body = asset_server.load("sprites/drafts/body_dark.png");
+    let dark_body = TextureAtlas::from_grid(dark_body, Vec2::new(96.0, 96.0), 5, 36);
+    let dark_body = texture_atlases.add(dark_body);
+
+    let head = asset_server.load("sprites/drafts/head_v3.png");
+    let head = TextureAtlas::from_grid(head, Vec2::new(96.0, 96.0), 4, 1);
+    let head = texture_atlases.add(head);
+
+    let tail = asset_server.load("sprites/drafts/tail_v2.png");
+    let tail = TextureAtlas::from_grid(tail, Vec2::new(96.0, 96.0), 4, 1);
+    let tail = texture_atlases.add(tail);
+
+    let glowing_body = asset_server.load("sprites/drafts/glowing_snake.png");
+    let glowing_body = TextureAtlas::from_grid(glowing_body, Vec2::new(32.0, 32.0), 6, 1);
+    let glowing_body = texture_atlases.add(glowing_body);
+
+    let head_to_orb = asset_server.load("sprites/drafts/orb.png");
+    let head_to_orb = TextureAtlas::from_grid(head_to_orb, Vec2::new(96.0, 96.0), 6, 5);
+    let head_to_orb = texture_atlases.add(head_to_orb);
+
+    let poison: Handle<ColorMaterial> =
+        materials.add(asset_server.load("sprites/drafts/poison.png").into());
+
+    let food: Handle<ColorMaterial> =
+        materials.add(asset_server.load("sprites/drafts/apple.png").into());
+
+    let ground: Handle<ColorMaterial> =
+        materials.add(asset_server.load("sprites/drafts/ground.png").into());
+
+    let wall: Handle<ColorMaterial> =
+        materials.add(asset_server.load("sprites/drafts/wall.png").into());
+
+    *snake_assets = MaybeSnakeAssets(Some(SnakeAssets {
+        head,
+        tail,
+        light_body,
+        dark_body,
+        glowing_body,
+        head_to_orb,
+        poison,
+        food,
+        ground,
+        wall,
+    }));
+}
+
+// there are no user levels on the web
+fn load_custom_levels(levels_dir: Option<Res<LevelsDir>>, mut packs: ResMut<Packs>) {
+    if cfg!(target_arch = "wasm32") {
+        return;
+    }
+
+    let dir = levels_dir
+        .map(|levels_dir| levels_dir.0.clone())
+        .unwrap_or_else(|| manifest::DEFAULT_LEVELS_DIR.into());
+    if let Err(e) = packs.add_custom(&dir) {
+        eprintln!(
+            "Failed to read levels from {}. Error was {}",
+            dir.display(),
+            e
+        );
+    }
+}
+
+fn setup(
+    asset_server: Res<AssetServer>,
+    packs: Res<Packs>,
+    level: Res<Selected>,
+    mut scene_spawner: ResMut<SceneSpawner>,
+    mut bg_color: ResMut<ClearColor>,
+) {
+    let scene_path = match packs.scene_path(&level.1) {
+        Some(scene_path) => scene_path,
+        None => {
+            eprintln!("Level {} is not in any level pack", level.1 .0);
+            return;
+        }
+    };
+
+    let scene_handle: Handle<DynamicScene> = asset_server.load(scene_path.as_str());
+    scene_spawner.spawn_dynamic(scene_handle);
+
+    *bg_color = ClearColor(Color::rgb(87. / 255., 114. / 255., 119. / 255.));
+}
+
+// spawning scenes is async, we don't have a good callback yet
+fn cleanup(
+    mut commands: Commands,
+    mut snake_parts: ResMut<SnakeParts>,
+
+    snake_assets: Res<MaybeSnakeAssets>,
+
+    grounds: Query<(&Ground, &GridLocation, Entity), Without<Sprite>>,
+    snakes: Query<
+        (&Snake, &GridLocation, Option<&SnakeSegment>, Entity),
+        Without<TextureAtlasSprite>,
+    >,
+    foods: Query<(&Food, &GridLocation, Entity), Without<Sprite>>,
+    poisons: Query<(&Poison, &GridLocation, Entity), Without<Sprite>>,
+) {
+    if snake_assets.0.is_none() {
+        return;
+    }
+
+    let snake_assets = snake_assets.0.as_ref().expect("fully loaded");
+
+    for (_ground, grid_location, e) in grounds.iter() {
+        commands.entity(e).insert_bundle(SpriteBundle {
+            sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+            material: snake_assets.ground.clone(),
+            transform: Transform::from_translation(Vec3::new(
+                grid_location.x as f32 * GRID_WIDTH,
+                grid_location.y as f32 * GRID_HEIGHT,
+                0.,
+            )),
+            ..Default::default()
+        });
+    }
+
+    for (_food, grid_location, e) in foods.iter() {
+        commands.entity(e).insert_bundle(SpriteBundle {
+            sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+            material: snake_assets.food.clone(),
+            transform: Transform::from_translation(Vec3::new(
+                grid_location.x as f32 * GRID_WIDTH,
+                grid_location.y as f32 * GRID_HEIGHT,
+                0.,
+            )),
+            ..Default::default()
+        });
+    }
+
+    for (_poison, grid_location, e) in poisons.iter() {
+        commands.entity(e).insert_bundle(SpriteBundle {
+            sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+            material: snake_assets.poison.clone(),
+            transform: Transform::from_translation(Vec3::new(
+                grid_location.x as f32 * GRID_WIDTH,
+                grid_location.y as f32 * GRID_HEIGHT,
+                0.,
+            )),
+            ..Default::default()
+        });
+    }
+
+    // TODO: use MapEntities
+    let segments = snakes
+        .iter()
+        .map(|(_snake, grid_location, segment, e)| {
+            (
+                grid_location.clone(),
+                segment.map(|segment| segment.index),
+                (e, grid_location.clone()),
+            )
+        })
+        .collect();
+    let internal_snake_parts = level::snake_order(segments);
+
+    if !internal_snake_parts.is_empty() {
+        let locations: Vec<GridLocation> = internal_snake_parts
+            .iter()
+            .map(|(_e, grid_location)| grid_location.clone())
+            .collect();
+        let tail = internal_snake_parts.len() - 1;
+
+        for (index, ((e, grid_location), orientation)) in internal_snake_parts
+            .iter()
+            .zip(initial_orientations(&locations))
+            .enumerate()
+        {
+            let (texture_atlas, z) = match index {
+                0 => (snake_assets.head.clone(), 0.),
+                x if x == tail => (snake_assets.tail.clone(), 1.),
+                x if x % 2 == 1 => (snake_assets.dark_body.clone(), 0.),
+                _ => (snake_assets.light_body.clone(), 0.),
+            };
+
+            // body sprites are picked from their transition; start them at rest
+            let transitions = if index == 0 || index == tail {
+                vec![]
+            } else {
+                vec![resting_transition(orientation)]
+            };
+
+            commands
+                .entity(*e)
+                .insert(LocationQueue(vec![]))
+                .insert(Tween::default())
+                .insert(TransitionQueue(transitions))
+                .insert(orientation)
+                .insert_bundle(SpriteSheetBundle {
+                    texture_atlas,
+                    transform: Transform::from_translation(Vec3::new(
+                        grid_location.x as f32 * GRID_WIDTH,
+                        grid_location.y as f32 * GRID_HEIGHT,
+                        z,
+                    )),
+                    ..Default::default()
+                });
+        }
+
+        *snake_parts = SnakeParts(internal_snake_parts.iter().map(|(e, _)| *e).collect());
+    }
+}
+
+// each segment points `to` the segment ahead of it and `from` the one behind it;
+// the head points away from its neck, the tail towards the body.
+fn initial_orientations(locations: &[GridLocation]) -> Vec<Orientation> {
+    let mut orientations = vec![];
+    for index in 0..locations.len() {
+        let towards_head = match index {
+            0 => None,
+            _ => direction_between(&locations[index], &locations[index - 1]),
+        };
+        let towards_tail = locations
+            .get(index + 1)
+            .and_then(|next| direction_between(&locations[index], next));
+
+        orientations.push(Orientation {
+            from: towards_tail
+                .or_else(|| towards_head.map(opposite))
+                .unwrap_or(Direction::Left),
+            to: towards_head
+                .or_else(|| towards_tail.map(opposite))
+                .unwrap_or(Direction::Right),
+        });
+    }
+    orientations
+}
+
+fn direction_between(from: &GridLocation, to: &GridLocation) -> Option<Direction> {
+    match (to.x - from.x, to.y - from.y) {
+        (0, 1) => Some(Direction::Up),
+        (0, -1) => Some(Direction::Down),
+        (-1, 0) => Some(Direction::Left),
+        (1, 0) => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+    }
+}
+
+// the last frame of a move that ends in `orientation`
+fn resting_transition(orientation: Orientation) -> Transition {
+    Transition {
+        from: Orientation {
+            from: orientation.from,
+            to: opposite(orientation.from),
+        },
+        to: orientation,
+        index: 3,
+    }
+}
+
+fn read_controls(
+    actions: Res<Actions>,
+    playback: Option<Res<Playback>>,
+    mut inputs: EventWriter<PlayerInput>,
+) {
+    // a replay is driving the snake
+    if playback.is_some() {
+        return;
+    }
+
+    if actions.just_pressed(Action::Reset) {
+        inputs.send(PlayerInput::Reset);
+    } else if actions.just_pressed(Action::Redo) {
+        inputs.send(PlayerInput::Redo);
+    } else if actions.just_pressed(Action::Undo) {
+        inputs.send(PlayerInput::Undo);
+    } else if actions.just_pressed(Action::SwitchBranch) {
+        inputs.send(PlayerInput::SwitchBranch);
+    }
+
+    let mut direction = None;
+    if actions.just_pressed(Action::Left) {
+        direction = Some(Direction::Left);
+    }
+    if actions.just_pressed(Action::Right) {
+        direction = Some(Direction::Right);
+    }
+    if actions.just_pressed(Action::Down) {
+        direction = Some(Direction::Down);
+    }
+    if actions.just_pressed(Action::Up) {
+        direction = Some(Direction::Up);
+    }
+
+    if let Some(direction) = direction {
+        inputs.send(PlayerInput::Move(direction));
+    }
+}
+
+// holds inputs made while anything is still moving or falling, and lets
+// them through one a frame once it has all come to rest
+fn buffer_inputs(
+    mut inputs: EventReader<PlayerInput>,
+    mut buffer: ResMut<InputBuffer>,
+    mut buffered: EventWriter<BufferedInput>,
+    queues: Query<&LocationQueue>,
+) {
+    for input in inputs.iter() {
+        buffer.push(*input);
+    }
+
+    if queues.iter().all(|queue| queue.0.is_empty()) {
+        if let Some(input) = buffer.pop() {
+            buffered.send(BufferedInput(input));
+        }
+    }
+}
+
+fn clear_input_buffer(mut buffer: ResMut<InputBuffer>) {
+    buffer.clear();
+}
+
+fn snake_movement(
+    mut inputs: EventReader<BufferedInput>,
+    mut accepted: EventWriter<AcceptedInput>,
+    snake_parts: Res<SnakeParts>,
+    grounds: Query<&GridLocation, (With<Ground>, Without<Snake>)>,
+
+    mut snakes: Query<
+        (&mut GridLocation, &mut LocationQueue, &mut Orientation),
+        (With<Snake>, Without<Ground>),
+    >,
+
+    mut transitions: Query<&mut TransitionQueue, With<Snake>>,
+
+    mut audio_cues: EventWriter<AudioCue>,
+) {
+    let mut direction = None;
+    for BufferedInput(input) in inputs.iter() {
+        if let PlayerInput::Move(input) = input {
+            direction = Some(*input);
+        }
+    }
+
+    let direction = match direction {
+        Some(direction) => direction,
+        None => return,
+    };
+    // segments spawned by food or undo this frame aren't there yet
+    let snake = match snake_parts
+        .0
+        .iter()
+        .map(|e| snakes.get_mut(*e).map(|(location, ..)| location.clone()))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(snake) => snake,
+        Err(_) => return,
+    };
+    let mut state = PuzzleState::new(snake, vec![], vec![]);
+    if !state.step(&board(grounds.iter()), direction) {
+        return;
+    }
+
+    audio_cues.send(AudioCue::Move);
+    accepted.send(AcceptedInput(PlayerInput::Move(direction)));
+
+    for (e, location) in snake_parts.0.iter().zip(state.snake) {
+        if let Ok((mut grid_location, mut queue, _orientation)) = snakes.get_mut(*e) {
+            if *grid_location != location {
+                *grid_location = location.clone();
+                queue.0.push(location);
+            }
+        }
+    }
+
+    if snake_parts.0.len() > 1 {
+        for (prev, curr) in snake_parts.0[1..]
+            .iter()
+            .zip(snake_parts.0[2..].iter())
+            .rev()
+        {
+            if let Ok((_prev_grid_location, _prev_queue, prev_orientation)) = snakes.get_mut(*prev)
+            {
+                let tmp = *prev_orientation;
+
+                if let Ok((_curr_grid_location, _curr_queue, mut curr_orientation)) =
+                    snakes.get_mut(*curr)
+                {
+                    if let Ok(mut transition_queue) = transitions.get_mut(*curr) {
+                        transition_queue.0.push(Transition {
+                            from: *curr_orientation,
+                            to: tmp,
+                            index: 0,
+                        });
+                    }
+
+                    *curr_orientation = tmp;
+                }
+            }
+        }
+    }
+
+    if let Some(second) = snake_parts.0.get(1) {
+        if let Ok((_grid_location, _queue, mut orientation)) = snakes.get_mut(*second) {
+            // handle transition here?
+
+            let old_orientation = *orientation;
+
+            orientation.from = match orientation.to {
+                Direction::Up => Direction::Down,
+                Direction::Down => Direction::Up,
+                Direction::Left => Direction::Right,
+                Direction::Right => Direction::Left,
+            };
+            orientation.to = direction;
+
+            let new_orientation = *orientation;
+
+            if let Ok(mut transition_queue) = transitions.get_mut(*second) {
+                transition_queue.0.push(Transition {
+                    from: old_orientation,
+                    to: new_orientation,
+                    index: 0,
+                });
+            }
+        }
+    }
+
+    if let Some(head) = snake_parts.0.first() {
+        if let Ok((_grid_location, _queue, mut orientation)) = snakes.get_mut(*head) {
+            let old_orientation = *orientation;
+            orientation.to = direction;
+            orientation.from = match direction {
+                Direction::Up => Direction::Down,
+                Direction::Down => Direction::Up,
+                Direction::Left => Direction::Right,
+                Direction::Right => Direction::Left,
+            };
+
+            // want to keep this updated so that undoing has latest sprite
+            transitions
+                .get_mut(*head)
+                .expect("head transition queue")
+                .0
+                .push(Transition {
+                    from: old_orientation,
+                    to: *orientation,
+                    index: 4,
+                })
+        }
+    }
+}
+
+// the rules' view of the ground on screen
+fn board<'a>(grounds: impl Iterator<Item = &'a GridLocation>) -> Board {
+    Board::new(grounds.cloned().collect())
+}
+
+fn gravity(
+    snake_parts: Res<SnakeParts>,
+    grounds: Query<&GridLocation, (With<Ground>, Without<Snake>)>,
+    mut snakes: Query<(&mut GridLocation, &mut LocationQueue), (With<Snake>, Without<Ground>)>,
+) {
+    let snake = match snake_parts
+        .0
+        .iter()
+        .map(|e| snakes.get_mut(*e).map(|(location, _)| location.clone()))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(snake) => snake,
+        Err(_) => return,
+    };
+    let mut state = PuzzleState::new(snake, vec![], vec![]);
+    if state.fall(&board(grounds.iter())) == 0 {
+        return;
+    }
+
+    for (e, location) in snake_parts.0.iter().zip(state.snake) {
+        if let Ok((mut grid_location, mut queue)) = snakes.get_mut(*e) {
+            *grid_location = location.clone();
+            queue.0.push(location);
+        }
+    }
+}
+
+fn food(
+    mut commands: Commands,
+
+    mut snake_parts: ResMut<SnakeParts>,
+
+    snake_assets: Res<MaybeSnakeAssets>,
+
+    snake_locations: Query<(&GridLocation, &Transform, &Orientation), (With<Snake>, Without<Food>)>,
+    food_locations: Query<(&GridLocation, Entity), (With<Food>, Without<Snake>)>,
+
+    mut audio_cues: EventWriter<AudioCue>,
+) {
+    if snake_assets.0.is_none() {
+        return;
+    }
+
+    let snake_assets = snake_assets.0.as_ref().expect("fully loaded");
+
+    if snake_parts.0.is_empty() {
+        return;
+    }
+
+    let snake = match snake_parts
+        .0
+        .iter()
+        .map(|e| {
+            snake_locations
+                .get(*e)
+                .map(|(location, ..)| location.clone())
+        })
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(snake) => snake,
+        Err(_) => return,
+    };
+    let (foods, food_entities): (Vec<_>, Vec<_>) = food_locations
+        .iter()
+        .map(|(location, e)| (location.clone(), e))
+        .unzip();
+
+    let mut state = PuzzleState::new(snake, foods, vec![]);
+    let Eaten { food, segment } = match state.eat_food() {
+        Some(eaten) => eaten,
+        None => return,
+    };
+
+    audio_cues.send(AudioCue::Apple);
+
+    // despawn food!
+    commands.entity(food_entities[food]).despawn_recursive();
+
+    let (_tail_location, tail_xform, tail_orientation) = snake_locations
+        .get(*snake_parts.0.last().expect("tail exists"))
+        .expect("tail has grid location");
+
+    let texture_atlas = {
+        if snake_parts.0.len() == 1 {
+            snake_assets.tail.clone()
+        } else if snake_parts.0.len() % 2 == 0 {
+            snake_assets.dark_body.clone()
+        } else {
+            snake_assets.light_body.clone()
+        }
+    };
+
+    let mut xform = *tail_xform;
+    xform.translation.z = 0.;
+
+    let location = state.snake[segment].clone();
+    let new_snake = commands
+        .spawn()
+        .insert_bundle(SpriteSheetBundle {
+            texture_atlas,
+            transform: xform,
+            ..Default::default()
+        })
+        .insert(location.clone())
+        // transforms only update is queue is nonempty...
+        .insert(LocationQueue(vec![location]))
+        .insert(Tween::default())
+        .insert(TransitionQueue(vec![]))
+        .insert(Snake)
+        // what is orientation??
+        .insert(*tail_orientation)
+        .id();
+
+    snake_parts.0.insert(segment, new_snake);
+}
+
+fn poison(
+    mut commands: Commands,
+
+    mut snake_parts: ResMut<SnakeParts>,
+
+    mut snake_locations: Query<
+        (&mut GridLocation, &Transform, &Orientation),
+        (With<Snake>, Without<Poison>),
+    >,
+
+    mut snake_location_queues: Query<&mut LocationQueue>,
+    mut snake_transition_queues: Query<&mut TransitionQueue>,
+
+    poison_locations: Query<(&GridLocation, Entity), (With<Poison>, Without<Snake>)>,
+) {
+    if snake_parts.0.is_empty() {
+        return;
+    }
+
+    let mut snake = vec![];
+    for e in snake_parts.0.iter() {
+        match snake_locations.get_mut(*e) {
+            Ok((location, ..)) => snake.push(location.clone()),
+            Err(_) => return,
+        }
+    }
+    let (poisons, poison_entities): (Vec<_>, Vec<_>) = poison_locations
+        .iter()
+        .map(|(location, e)| (location.clone(), e))
+        .unzip();
+
+    let mut state = PuzzleState::new(snake, vec![], poisons);
+    let Poisoned { poison, segment } = match state.eat_poison() {
+        Some(poisoned) => poisoned,
+        None => return,
+    };
+
+    // despawn poison!
+    commands.entity(poison_entities[poison]).despawn_recursive();
+
+    let to_despawn = snake_parts.0.remove(segment);
+    let (_to_despawn_loc, _xform, to_despawn_orientation) = snake_locations
+        .get_mut(to_despawn)
+        .expect("still exists for now");
+    let new_tail_orientation = *to_despawn_orientation;
+
+    commands.entity(to_despawn).despawn_recursive();
+
+    if snake_parts.0.len() > 1 {
+        let new_tail_location = state.snake.last().expect("tail exists").clone();
+
+        let tail_entity = *snake_parts.0.last().expect("tail exists");
+        let (mut tail_location, _tail_xform, tail_orientation) =
+            snake_locations.get_mut(tail_entity).expect("tail lookup");
+
+        *tail_location = new_tail_location.clone();
+
+        let mut tail_location_queue = snake_location_queues
+            .get_mut(tail_entity)
+            .expect("tail lookup");
+        tail_location_queue.0.push(new_tail_location);
+
+        let mut tail_transition_queue = snake_transition_queues
+            .get_mut(tail_entity)
+            .expect("tail lookup");
+        tail_transition_queue.0.push(Transition {
+            from: *tail_orientation,
+            to: new_tail_orientation,
+            index: 0,
+        });
+    }
+}
+
+fn gridlocation_to_transform(
+    time: Res<Time>,
+    animation: Res<MoveAnimation>,
+    mut q: Query<(&mut LocationQueue, &mut Tween, &mut Transform)>,
+) {
+    for (mut location_queue, mut tween, mut xform) in q.iter_mut() {
+        if location_queue.0.is_empty() {
+            continue;
+        }
+
+        let at = animation.advance(
+            xform.translation.truncate(),
+            &mut location_queue.0,
+            &mut tween,
+            time.delta_seconds(),
+        );
+        xform.translation.x = at.x;
+        xform.translation.y = at.y;
+    }
+}
+
+fn win(
+    snake_parts: Res<SnakeParts>,
+    snake_locations: Query<(&GridLocation, &Transform), With<Snake>>,
+    mut state: ResMut<State<GameState>>,
+
+    mut audio_cues: EventWriter<AudioCue>,
+) {
+    let snake = match snake_parts
+        .0
+        .iter()
+        .map(|e| {
+            snake_locations
+                .get(*e)
+                .map(|(location, _)| location.clone())
+        })
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(snake) => snake,
+        Err(_) => return,
+    };
+    if !rules::is_won(&snake) {
+        return;
+    }
+
+    if let Ok((_head_location, head_xform)) =
+        snake_locations.get(*snake_parts.0.first().expect("snake head exists"))
+    {
+        let (_tail_location, tail_xform) = snake_locations
+            .get(*snake_parts.0.last().expect("snake tail exists"))
+            .expect("tail has location");
+
+        // ignore z in this calculation!
+        if head_xform
+            .translation
+            .truncate()
+            .distance(tail_xform.translation.truncate())
+            < 0.001
+        {
+            audio_cues.send(AudioCue::Win);
+
+            println!("You won! Nice.");
+            state.set(GameState::Win).ok();
+        }
+    }
+}
+
+fn sprite(
+    time: Res<Time>,
+    animation: Res<MoveAnimation>,
+    snake_parts: Res<SnakeParts>,
+    // body frames due but not yet shown, carried between quick frames
+    mut owed: Local<f32>,
+
+    // TODO: shouldn't need both of these once everything has a transition (???)
+    mut orientation_query: Query<(&Orientation, &mut TextureAtlasSprite, &mut TransitionQueue)>,
+) {
+    // a body animation plays its frames over the time one move takes
+    *owed += time.delta_seconds() * animation.tiles_per_second * BODY_FRAMES as f32;
+    let frames = owed.floor() as u32;
+    *owed -= frames as f32;
+
+    for (index, e) in snake_parts.0.iter().enumerate() {
+        let tail = snake_parts.0.len() - 1;
+        match index {
+            // HEAD
+            0 => match orientation_query.get_mut(*e) {
+                Ok((orientation, mut sprite, _queue)) => sprite.index = head_index(orientation.to),
+                Err(_) => {
+                    dbg!("Someone should look into this...");
+                }
+            },
+            // TAIL
+            x if x == tail => match orientation_query.get_mut(*e) {
+                Ok((orientation, mut sprite, _queue)) => sprite.index = tail_index(orientation.to),
+                Err(_) => {
+                    dbg!("Someone should look into this...");
+                }
+            },
+            // TWEENER
+            _ => match orientation_query.get_mut(*e) {
+                // increment transition; match transition from and to to determine offset
+                // offset depends on transition values;
+                Ok((_orientation, mut sprite, mut transition_queue)) => {
+                    let mut frames = frames;
+                    while let Some(transition) = transition_queue.0.first_mut() {
+                        // TODO: this is a function of transition to / from
+
+                        let offset = transition_offset(transition).unwrap_or_else(|| {
+                            dbg!("missed one");
+                            dbg!(transition.clone());
+                            1000
+                        });
+
+                        let step = frames.min(BODY_FRAMES - transition.index);
+                        transition.index += step;
+                        frames -= step;
+
+                        sprite.index = offset * 5 + transition.index;
+
+                        if transition.index < BODY_FRAMES {
+                            break;
+                        }
+                        transition_queue.0.remove(0);
+                    }
+                }
+                Err(_) => {
+                    dbg!("Someone should look into this...");
+                }
+            },
+        }
+    }
+}
+
+// the last frame of a body animation, which is also how many it steps through
+const BODY_FRAMES: u32 = 4;
+
+fn head_index(facing: Direction) -> u32 {
+    match facing {
+        Direction::Up => 1,
+        Direction::Down => 3,
+        Direction::Left => 2,
+        Direction::Right => 0,
+    }
+}
+
+fn tail_index(facing: Direction) -> u32 {
+    match facing {
+        Direction::Up => 3,
+        Direction::Down => 1,
+        Direction::Left => 0,
+        Direction::Right => 2,
+    }
+}
+
+// which of the 36 body animations plays for a transition
+fn transition_offset(transition: &Transition) -> Option<u32> {
+    match (transition.from, transition.to) {
+        (
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Right,
+            },
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Right,
+            },
+        ) => Some(0),
+
+        (
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Right,
+            },
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Up,
+            },
+        ) => Some(1),
+
+        (
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Right,
+            },
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Down,
+            },
+        ) => Some(2),
+
+        (
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Up,
+            },
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Left,
+            },
+        ) => Some(3),
+
+        (
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Up,
+            },
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Up,
+            },
+        ) => Some(4),
+
+        (
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Up,
+            },
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Right,
+            },
+        ) => Some(5),
+
+        (
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Down,
+            },
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Left,
+            },
+        ) => Some(6),
+
+        (
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Down,
+            },
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Down,
+            },
+        ) => Some(7),
+
+        (
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Down,
+            },
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Right,
+            },
+        ) => Some(8),
+
+        (
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Left,
+            },
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Left,
+            },
+        ) => Some(9),
+
+        (
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Left,
+            },
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Up,
+            },
+        ) => Some(10),
+
+        (
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Left,
+            },
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Down,
+            },
+        ) => Some(11),
+
+        (
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Up,
+            },
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Left,
+            },
+        ) => Some(12),
+
+        (
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Up,
+            },
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Up,
+            },
+        ) => Some(13),
+
+        (
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Up,
+            },
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Right,
+            },
+        ) => Some(14),
+
+        (
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Down,
+            },
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Left,
+            },
+        ) => Some(15),
+
+        (
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Down,
+            },
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Down,
+            },
+        ) => Some(16),
+
+        (
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Down,
+            },
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Right,
+            },
+        ) => Some(17),
+
+        (
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Up,
+            },
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Up,
+            },
+        ) => Some(18),
+
+        (
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Up,
+            },
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Left,
+            },
+        ) => Some(19),
+
+        (
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Up,
+            },
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Right,
+            },
+        ) => Some(20),
+
+        (
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Left,
+            },
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Up,
+            },
+        ) => Some(21),
+
+        (
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Left,
+            },
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Left,
+            },
+        ) => Some(22),
+
+        (
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Left,
+            },
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Down,
+            },
+        ) => Some(23),
+
+        (
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Right,
+            },
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Up,
+            },
+        ) => Some(24),
+
+        (
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Right,
+            },
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Right,
+            },
+        ) => Some(25),
+
+        (
+            Orientation {
+                from: Direction::Down,
+                to: Direction::Right,
+            },
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Down,
+            },
+        ) => Some(26),
+
+        (
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Down,
+            },
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Down,
+            },
+        ) => Some(27),
+
+        (
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Down,
+            },
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Left,
+            },
+        ) => Some(28),
+
+        (
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Down,
+            },
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Right,
+            },
+        ) => Some(29),
+
+        (
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Left,
+            },
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Up,
+            },
+        ) => Some(30),
+
+        (
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Left,
+            },
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Left,
+            },
+        ) => Some(31),
+
+        (
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Left,
+            },
+            Orientation {
+                from: Direction::Right,
+                to: Direction::Down,
+            },
+        ) => Some(32),
+
+        (
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Right,
+            },
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Up,
+            },
+        ) => Some(33),
+
+        (
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Right,
+            },
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Right,
+            },
+        ) => Some(34),
+
+        (
+            Orientation {
+                from: Direction::Up,
+                to: Direction::Right,
+            },
+            Orientation {
+                from: Direction::Left,
+                to: Direction::Down,
+            },
+        ) => Some(35),
+        _ => None,
+    }
+}
+
+// the atlas, frame and depth of each segment of a snake that isn't moving,
+// head first; the editor draws snakes with these
+pub(crate) fn resting_sprites(
+    snake_assets: &SnakeAssets,
+    locations: &[GridLocation],
+) -> Vec<(Handle<TextureAtlas>, u32, f32)> {
+    let tail = locations.len().saturating_sub(1);
+
+    initial_orientations(locations)
+        .into_iter()
+        .enumerate()
+        .map(|(index, orientation)| match index {
+            0 => (snake_assets.head.clone(), head_index(orientation.to), 0.),
+            x if x == tail => (snake_assets.tail.clone(), tail_index(orientation.to), 1.),
+            x => {
+                let body = if x % 2 == 1 {
+                    snake_assets.dark_body.clone()
+                } else {
+                    snake_assets.light_body.clone()
+                };
+                // segments that don't line up have no animation; show any frame
+                let offset = transition_offset(&resting_transition(orientation)).unwrap_or(0);
+                (body, offset * 5 + 4, 0.)
+            }
+        })
+        .collect()
+}
+
+fn update_history(
+    mut commands: Commands,
+
+    mut history: ResMut<GameHistory>,
+    mut snake_parts: ResMut<SnakeParts>,
+    mut inputs: EventReader<BufferedInput>,
+    mut accepted: EventWriter<AcceptedInput>,
+    snake_assets: Res<MaybeSnakeAssets>,
+
+    snake_query: Query<(&GridLocation, &TransitionQueue), With<Snake>>,
+    food_query: Query<(Entity, &GridLocation), With<Food>>,
+    poison_query: Query<(Entity, &GridLocation), With<Poison>>,
+) {
+    if snake_assets.0.is_none() {
+        return;
+    }
+
+    let snake_assets = snake_assets.0.as_ref().expect("loaded");
+
+    let mut history_input = None;
+    for BufferedInput(input) in inputs.iter() {
+        match input {
+            PlayerInput::Move(_) => {}
+            input => history_input = Some(*input),
+        }
+    }
+
+    if let (Some(input), Some(game_history)) = (history_input, history.0.as_mut()) {
+        let snapshot = match input {
+            PlayerInput::Reset => Some(game_history.reset()),
+            PlayerInput::Undo => game_history.undo(),
+            PlayerInput::Redo => game_history.redo(),
+            PlayerInput::SwitchBranch => game_history.switch_branch(),
+            PlayerInput::Move(_) => None,
+        };
+
+        if let Some(snapshot) = snapshot {
+            accepted.send(AcceptedInput(input));
+
+            restore_snapshot(
+                &mut commands,
+                snapshot,
+                snake_assets,
+                &mut snake_parts,
+                &food_query,
+                &poison_query,
+            );
+        }
+        return;
+    }
+
+    // update history with current snapshot if necessary
+    let head = match snake_parts.0.first() {
+        Some(head) => *head,
+        None => return,
+    };
+    // FIX THIS;
+    let head_grid_location = match snake_query.get(head) {
+        Ok((head_grid_location, _transition)) => head_grid_location,
+        Err(_) => return,
+    };
+
+    if let Some(game_history) = &history.0 {
+        match game_history.current().snakes.first() {
+            Some((snapshot_grid_location, _transition)) => {
+                if head_grid_location == snapshot_grid_location {
+                    return;
+                }
+                dbg!("New state.");
+            }
+            None => {
+                // head appeared from nothing? That's unexpected...
+                eprintln!("Unexpected behavior. Head appeared from nothing");
+                return;
+            }
+        }
+    } else {
+        dbg!("History is empty; bootstrapping");
+    }
+
+    let mut snakes = vec![];
+    for e in snake_parts.0.iter() {
+        let (grid_location, transition) = snake_query.get(*e).expect("snake part lookup");
+
+        let mut transition = transition.0.last().cloned().unwrap_or(Transition {
+            from: Orientation {
+                from: Direction::Left,
+                to: Direction::Right,
+            },
+            to: Orientation {
+                from: Direction::Left,
+                to: Direction::Right,
+            },
+            index: 4,
+        });
+
+        transition.index = 4;
+
+        snakes.push((grid_location.clone(), transition));
+    }
+
+    let mut foods = vec![];
+    for (_e, grid_location) in food_query.iter() {
+        foods.push(grid_location.clone());
+    }
+
+    let mut poisons = vec![];
+    for (_e, grid_location) in poison_query.iter() {
+        poisons.push(grid_location.clone());
+    }
+
+    let snapshot = Snapshot {
+        snakes,
+        foods,
+        poisons,
+    };
+    match history.0.as_mut() {
+        Some(game_history) => game_history.push(snapshot),
+        None => history.0 = Some(History::new(snapshot)),
+    }
+}
+
+// replace the snake, food and poison on screen with the ones in `snapshot`
+fn restore_snapshot(
+    commands: &mut Commands,
+    snapshot: &Snapshot,
+    snake_assets: &SnakeAssets,
+    snake_parts: &mut SnakeParts,
+    food_query: &Query<(Entity, &GridLocation), With<Food>>,
+    poison_query: &Query<(Entity, &GridLocation), With<Poison>>,
+) {
+    for e in snake_parts.0.iter() {
+        commands.entity(*e).despawn_recursive();
+    }
+
+    for (e, _grid_location) in food_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    for (e, _grid_location) in poison_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    let mut new_snake_parts = vec![];
+
+    for (index, (grid_location, transition)) in snapshot.snakes.iter().enumerate() {
+        let handle = match index {
+            0 => snake_assets.head.clone(),
+            n if n == (snapshot.snakes.len() - 1) => snake_assets.tail.clone(),
+            n if n % 2 == 0 => snake_assets.light_body.clone(),
+            _ => snake_assets.dark_body.clone(),
+        };
+
+        let z = {
+            if index == snapshot.snakes.len() - 1 {
+                1.
+            } else {
+                0.
+            }
+        };
+
+        new_snake_parts.push(
+            commands
+                .spawn()
+                .insert_bundle(SpriteSheetBundle {
+                    texture_atlas: handle,
+                    transform: Transform::from_translation(Vec3::new(
+                        grid_location.x as f32 * GRID_WIDTH,
+                        grid_location.y as f32 * GRID_WIDTH,
+                        z,
+                    )),
+                    ..Default::default()
+                })
+                .insert(grid_location.clone())
+                .insert(LocationQueue(vec![]))
+                .insert(Tween::default())
+                .insert(TransitionQueue(vec![transition.clone()]))
+                .insert(transition.to)
+                .insert(Snake)
+                .id(),
+        );
+    }
+
+    *snake_parts = SnakeParts(new_snake_parts);
+
+    for food_grid_location in snapshot.foods.iter() {
+        commands
+            .spawn()
+            .insert_bundle(SpriteBundle {
+                sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+                material: snake_assets.food.clone(),
+                transform: Transform::from_translation(Vec3::new(
+                    food_grid_location.x as f32 * GRID_WIDTH,
+                    food_grid_location.y as f32 * GRID_WIDTH,
+                    0.,
+                )),
+                ..Default::default()
+            })
+            .insert(food_grid_location.clone())
+            .insert(Food);
+    }
+
+    for poison_grid_location in snapshot.poisons.iter() {
+        commands
+            .spawn()
+            .insert_bundle(SpriteBundle {
+                sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+                material: snake_assets.poison.clone(),
+                transform: Transform::from_translation(Vec3::new(
+                    poison_grid_location.x as f32 * GRID_WIDTH,
+                    poison_grid_location.y as f32 * GRID_WIDTH,
+                    0.,
+                )),
+                ..Default::default()
+            })
+            .insert(poison_grid_location.clone())
+            .insert(Poison);
+    }
+}
+
+struct GlowingSnake;
+
+struct HeadToOrb;
+
+struct SnakePartTarget(usize);
+
+// spawn a bunch of GlowingSnakes
+// spawn head to orb transition
+fn enter_win(
+    mut commands: Commands,
+
+    snake_parts: Res<SnakeParts>,
+    snake_assets: Res<MaybeSnakeAssets>,
+
+    snakes: Query<(&Transform, &Orientation), With<Snake>>,
+) {
+    let snake_assets = snake_assets.0.as_ref().expect("snake assets");
+
+    {
+        let head = snake_parts.0.first().expect("head exists");
+
+        let (head_xform, head_orientation) = snakes.get(*head).expect("snake parts lookup");
+
+        let tail = snake_parts.0.last().expect("head exists");
+
+        let (_tail_xform, tail_orientation) = snakes.get(*tail).expect("snake parts lookup");
+
+        let glowing_index = glowing_index(head_orientation.from, tail_orientation.to);
+
+        let transform = *head_xform;
+        let color = Color::rgba(1.0, 1.0, 1.0, 0.0);
+
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    index: glowing_index,
+                    color,
+                    ..Default::default()
+                },
+                texture_atlas: snake_assets.glowing_body.clone(),
+                transform,
+                ..Default::default()
+            })
+            .insert(GlowingSnake);
+
+        let head_to_orb_index = match head_orientation.to {
+            Direction::Up => 3,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 0,
+        };
+
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    index: head_to_orb_index * 5,
+                    ..Default::default()
+                },
+                texture_atlas: snake_assets.head_to_orb.clone(),
+                transform,
+                ..Default::default()
+            })
+            .insert(SnakePartTarget(snake_parts.0.len() - 2))
+            .insert(Timer::from_seconds(0.1, true))
+            .insert(HeadToOrb);
+    }
+
+    for e in snake_parts.0[1..(snake_parts.0.len() - 1)].iter() {
+        let (xform, orientation) = snakes.get(*e).expect("snake parts lookup");
+
+        let index = glowing_index(orientation.from, orientation.to);
+
+        let transform = *xform;
+        let color = Color::rgba(1.0, 1.0, 1.0, 0.0);
+
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    color,
+                    index,
+                    ..Default::default()
+                },
+                texture_atlas: snake_assets.glowing_body.clone(),
+                transform,
+                ..Default::default()
+            })
+            .insert(GlowingSnake);
+    }
+}
+
+fn glowing_index(from: Direction, to: Direction) -> u32 {
+    match (from, to) {
+        (Direction::Up, Direction::Down) => 1,
+        (Direction::Up, Direction::Left) => 4,
+        (Direction::Up, Direction::Right) => 5,
+        (Direction::Down, Direction::Up) => 1,
+        (Direction::Down, Direction::Left) => 3,
+        (Direction::Down, Direction::Right) => 2,
+        (Direction::Left, Direction::Up) => 4,
+        (Direction::Left, Direction::Down) => 3,
+        (Direction::Left, Direction::Right) => 0,
+        (Direction::Right, Direction::Up) => 5,
+        (Direction::Right, Direction::Down) => 2,
+        (Direction::Right, Direction::Left) => 0,
+        _ => {
+            eprintln!("Unexpcted orientation in win");
+            1000
+        }
+    }
+}
+
+// fade out non-glowing snakes
+// orb transition
+// how quickly the win animation plays; it used to go a step a frame at 60fps
+const FADE_PER_SECOND: f32 = 6.0;
+const ORB_FRAMES_PER_SECOND: f32 = 60.0;
+const ORB_PIXELS_PER_SECOND: f32 = 60.0;
+
+fn update_win(
+    time: Res<Time>,
+    // orb frames due but not yet shown
+    mut owed: Local<f32>,
+
+    snake_parts: Res<SnakeParts>,
+
+    target_lookup: Query<&Transform, (With<Snake>, Without<HeadToOrb>)>,
+
+    mut snakes: Query<
+        &mut TextureAtlasSprite,
+        (With<Snake>, Without<GlowingSnake>, Without<HeadToOrb>),
+    >,
+    mut glowing_snakes: Query<
+        &mut TextureAtlasSprite,
+        (With<GlowingSnake>, Without<Snake>, Without<HeadToOrb>),
+    >,
+    mut head_to_orb: Query<
+        (
+            &mut SnakePartTarget,
+            &mut TextureAtlasSprite,
+            &mut Transform,
+            &mut Timer,
+        ),
+        (With<HeadToOrb>, Without<Snake>, Without<GlowingSnake>),
+    >,
+) {
+    // bye bye friends
+
+    let fade = FADE_PER_SECOND * time.delta_seconds();
+    for mut sprite in snakes.iter_mut() {
+        let new_a = (sprite.color.a() - fade).max(0.);
+        sprite.color.set_a(new_a);
+    }
+
+    for mut sprite in glowing_snakes.iter_mut() {
+        let new_a = (sprite.color.a() + fade).min(1.);
+        sprite.color.set_a(new_a);
+    }
+
+    *owed += ORB_FRAMES_PER_SECOND * time.delta_seconds();
+    let frames = owed.floor() as u32;
+    *owed -= frames as f32;
+
+    for (mut target, mut sprite, mut xform, mut timer) in head_to_orb.iter_mut() {
+        timer.tick(time.delta());
+
+        if sprite.index < 24 && sprite.index % 6 != 5 {
+            sprite.index = (sprite.index + frames).min(sprite.index - sprite.index % 6 + 5);
+        } else if sprite.index < 24 {
+            sprite.index = 24;
+        } else {
+            if timer.just_finished() {
+                sprite.index = 24 + ((1 + sprite.index) % 6);
+            }
+
+            if let Some(target_e) = snake_parts.0.get(target.0) {
+                if let Ok(target_xform) = target_lookup.get(*target_e) {
+                    // move towards target
+
+                    let delta = target_xform.translation - xform.translation;
+                    let step = ORB_PIXELS_PER_SECOND * time.delta_seconds();
+
+                    xform.translation.x += delta.x.clamp(-step, step);
+                    xform.translation.y += delta.y.clamp(-step, step);
+                    xform.translation.z += delta.z.clamp(-step, step);
+
+                    if xform.translation.distance(target_xform.translation) < 0.001 {
+                        dbg!("changing");
+                        target.0 = (snake_parts.0.len() + target.0 - 1) % snake_parts.0.len();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn quit_level(
+    mut commands: Commands,
+
+    mut state: ResMut<State<GameState>>,
+    quit_to: Res<QuitTo>,
+    mut snake_parts: ResMut<SnakeParts>,
+
+    mut actions: ResMut<Actions>,
+
+    grounds: Query<(&Ground, Entity)>,
+    snakes: Query<(&Snake, Entity)>,
+    foods: Query<(&Food, Entity)>,
+    poisons: Query<(&Poison, Entity)>,
+
+    glowers: Query<(&GlowingSnake, Entity)>,
+    orbs: Query<(&HeadToOrb, Entity)>,
+) {
+    if actions.just_pressed(Action::Back) {
+        // or the level select would take it as going back too
+        actions.clear();
+
+        for (_ground, e) in grounds.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+        for (_snake, e) in snakes.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+        for (_food, e) in foods.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+        for (_poison, e) in poisons.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+
+        for (_glow, e) in glowers.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+        for (_orb, e) in orbs.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+
+        snake_parts.0.clear();
+
+        state.set(quit_to.0.clone()).unwrap();
+    }
+}
+
+struct Wall;
+
+fn wall(mut commands: Commands, snake_assets: Res<MaybeSnakeAssets>) {
+    let snake_assets = snake_assets.0.as_ref().expect("fully loaded");
+
+    for x in -BOARD_RADIUS..=BOARD_RADIUS {
+        for y in -BOARD_RADIUS..=BOARD_RADIUS {
+            commands
+                .spawn()
+                .insert_bundle(SpriteBundle {
+                    sprite: Sprite::new(Vec2::new(GRID_WIDTH, GRID_HEIGHT)),
+                    material: snake_assets.wall.clone(),
+                    transform: Transform::from_translation(Vec3::new(
+                        x as f32 * GRID_WIDTH,
+                        y as f32 * GRID_HEIGHT,
+                        0.,
+                    )),
+                    ..Default::default()
+                })
+                .insert(Wall);
+        }
+    }
+}
+
+pub(crate) fn exit_ingame(
+    mut commands: Commands,
+    q: Query<(&Wall, Entity)>,
+    mut game_history: ResMut<GameHistory>,
+
+    mut audio_cues: EventWriter<AudioCue>,
+) {
+    for (_wall, e) in q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    // need to clear gamestate too!
+    *game_history = GameHistory(None);
+
+    audio_cues.send(AudioCue::Stop);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bevy::app::Events;
+
+    // the systems that play a move, in the order the game runs them
+    fn level_stage() -> SystemStage {
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system(update_history.system().label(HistoryLabel))
+            .add_system(food.system().label(FoodLabel).after(HistoryLabel))
+            .add_system(poison.system().label(PoisonLabel).after(FoodLabel))
+            .add_system(
+                snake_movement
+                    .system()
+                    .label(SnakeMovementLabel)
+                    .after(PoisonLabel),
+            )
+            .add_system(gravity.system().after(SnakeMovementLabel));
+        stage
+    }
+
+    fn level_world() -> World {
+        let mut world = World::default();
+        world.insert_resource(MaybeSnakeAssets(Some(SnakeAssets {
+            head: Default::default(),
+            tail: Default::default(),
+            light_body: Default::default(),
+            dark_body: Default::default(),
+            glowing_body: Default::default(),
+            head_to_orb: Default::default(),
+            poison: Default::default(),
+            food: Default::default(),
+            ground: Default::default(),
+            wall: Default::default(),
+        })));
+        world.insert_resource(GameHistory(None));
+        world.insert_resource(Events::<BufferedInput>::default());
+        world.insert_resource(Events::<AcceptedInput>::default());
+        world.insert_resource(Events::<AudioCue>::default());
+
+        for x in 0..5 {
+            world
+                .spawn()
+                .insert(GridLocation { x, y: 0 })
+                .insert(Ground);
+        }
+        world
+            .spawn()
+            .insert(GridLocation { x: 3, y: 1 })
+            .insert(Food);
+
+        let locations = vec![
+            GridLocation { x: 2, y: 1 },
+            GridLocation { x: 1, y: 1 },
+            GridLocation { x: 0, y: 1 },
+        ];
+        let orientations = initial_orientations(&locations);
+        let parts = locations
+            .into_iter()
+            .zip(orientations)
+            .map(|(location, orientation)| {
+                world
+                    .spawn()
+                    .insert(location)
+                    .insert(LocationQueue(vec![]))
+                    .insert(Tween::default())
+                    .insert(TransitionQueue(vec![]))
+                    .insert(orientation)
+                    .insert(Transform::default())
+                    .insert(Snake)
+                    .id()
+            })
+            .collect();
+        world.insert_resource(SnakeParts(parts));
+        world
+    }
+
+    fn press(world: &mut World, input: PlayerInput) {
+        world
+            .get_resource_mut::<Events<BufferedInput>>()
+            .unwrap()
+            .send(BufferedInput(input));
+    }
+
+    fn snake(world: &World) -> Vec<GridLocation> {
+        let parts = world.get_resource::<SnakeParts>().unwrap().0.clone();
+        parts
+            .iter()
+            .map(|e| world.get::<GridLocation>(*e).unwrap().clone())
+            .collect()
+    }
+
+    #[test]
+    fn eating_and_undoing_swap_segments_without_a_hitch() {
+        let mut world = level_world();
+        let mut stage = level_stage();
+        stage.run(&mut world);
+
+        press(&mut world, PlayerInput::Move(Direction::Right));
+        stage.run(&mut world);
+        // the head lands on the food, which it eats the frame after
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(snake(&world).len(), 4);
+
+        press(&mut world, PlayerInput::Undo);
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(
+            snake(&world),
+            vec![
+                GridLocation { x: 2, y: 1 },
+                GridLocation { x: 1, y: 1 },
+                GridLocation { x: 0, y: 1 },
+            ]
+        );
+        assert_eq!(world.query::<&Food>().iter(&world).count(), 1);
+    }
+}
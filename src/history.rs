@@ -0,0 +1,112 @@
+// Undo history that keeps every line of play.
+//
+// Undoing and then moving somewhere else starts a new branch instead of
+// throwing the undone moves away; redo follows whichever branch was visited
+// last, and switching branches hops between siblings at the same depth.
+
+#[derive(Debug)]
+pub struct History<T> {
+    nodes: Vec<Node<T>>,
+    current: usize,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    state: T,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    redo: Option<usize>,
+}
+
+impl<T> History<T> {
+    pub fn new(root: T) -> Self {
+        History {
+            nodes: vec![Node {
+                state: root,
+                parent: None,
+                children: vec![],
+                redo: None,
+            }],
+            current: 0,
+        }
+    }
+
+    pub fn current(&self) -> &T {
+        &self.nodes[self.current].state
+    }
+
+    pub fn push(&mut self, state: T) {
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            state,
+            parent: Some(self.current),
+            children: vec![],
+            redo: None,
+        });
+
+        let parent = &mut self.nodes[self.current];
+        parent.children.push(index);
+        parent.redo = Some(index);
+
+        self.current = index;
+    }
+
+    pub fn undo(&mut self) -> Option<&T> {
+        self.current = self.nodes[self.current].parent?;
+        Some(self.current())
+    }
+
+    pub fn redo(&mut self) -> Option<&T> {
+        self.current = self.nodes[self.current].redo?;
+        Some(self.current())
+    }
+
+    // back to the start; redo walks forward along the same moves again
+    pub fn reset(&mut self) -> &T {
+        self.current = 0;
+        self.current()
+    }
+
+    pub fn switch_branch(&mut self) -> Option<&T> {
+        let parent = self.nodes[self.current].parent?;
+        let siblings = &self.nodes[parent].children;
+        if siblings.len() < 2 {
+            return None;
+        }
+
+        let position = siblings
+            .iter()
+            .position(|sibling| *sibling == self.current)
+            .expect("child of its parent");
+        let next = siblings[(position + 1) % siblings.len()];
+
+        self.nodes[parent].redo = Some(next);
+        self.current = next;
+        Some(self.current())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::History;
+
+    #[test]
+    fn undone_moves_become_a_branch() {
+        let mut history = History::new(0);
+        history.push(1);
+        history.push(2);
+
+        assert_eq!(history.undo(), Some(&1));
+        history.push(3);
+        assert_eq!(history.redo(), None);
+
+        assert_eq!(history.switch_branch(), Some(&2));
+        assert_eq!(history.undo(), Some(&1));
+        assert_eq!(history.redo(), Some(&2));
+
+        assert_eq!(history.reset(), &0);
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(&1));
+        assert_eq!(history.redo(), Some(&2));
+    }
+}
@@ -0,0 +1,79 @@
+// Inputs made while the snake is still moving.
+//
+// Moves are only acted on once every sprite has reached its cell and the
+// snake has finished falling, so a quick typist never sees a half finished
+// move. Whatever they pressed in the meantime waits here and is let through
+// one input a frame, in order.
+
+use std::collections::VecDeque;
+
+use crate::PlayerInput;
+
+pub const DEFAULT_INPUT_BUFFER: usize = 3;
+
+pub struct InputBuffer {
+    // most inputs kept waiting; more are dropped
+    depth: usize,
+    pending: VecDeque<PlayerInput>,
+}
+
+impl Default for InputBuffer {
+    fn default() -> Self {
+        InputBuffer::new(DEFAULT_INPUT_BUFFER)
+    }
+}
+
+impl InputBuffer {
+    pub fn new(depth: usize) -> Self {
+        InputBuffer {
+            depth: depth.max(1),
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.pending.len() >= self.depth
+    }
+
+    pub(crate) fn push(&mut self, input: PlayerInput) {
+        // moves made before a reset would only be thrown away by it
+        if input == PlayerInput::Reset {
+            self.pending.clear();
+        }
+        if !self.is_full() {
+            self.pending.push_back(input);
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<PlayerInput> {
+        self.pending.pop_front()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InputBuffer;
+    use crate::{Direction, PlayerInput};
+
+    #[test]
+    fn keeps_inputs_in_order_up_to_its_depth() {
+        let mut buffer = InputBuffer::new(2);
+        buffer.push(PlayerInput::Move(Direction::Left));
+        buffer.push(PlayerInput::Undo);
+        assert!(buffer.is_full());
+        buffer.push(PlayerInput::Move(Direction::Up));
+
+        assert_eq!(buffer.pop(), Some(PlayerInput::Move(Direction::Left)));
+        assert_eq!(buffer.pop(), Some(PlayerInput::Undo));
+        assert_eq!(buffer.pop(), None);
+
+        buffer.push(PlayerInput::Move(Direction::Left));
+        buffer.push(PlayerInput::Reset);
+        assert_eq!(buffer.pop(), Some(PlayerInput::Reset));
+        assert_eq!(buffer.pop(), None);
+    }
+}
@@ -0,0 +1,312 @@
+// Reading level scenes without spinning up Bevy, for the command line tools.
+//
+// Scenes are the `DynamicScene` RON written by the editor: a list of entities,
+// each with reflected components keyed by type path.
+
+use ron::value::{Map, Number, Value};
+
+use std::any::type_name;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::rules::{Board, PuzzleState};
+use crate::{Food, GridLocation, Ground, Poison, Snake, SnakeSegment};
+
+#[derive(Clone, Debug)]
+pub struct SceneEntity {
+    pub entity: i64,
+    pub components: Vec<SceneComponent>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SceneComponent {
+    pub type_name: String,
+    pub fields: Vec<(String, Value)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Level {
+    pub board: Board,
+    pub state: PuzzleState,
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Ron(ron::Error),
+    Malformed(String),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(e) => write!(f, "couldn't read scene: {}", e),
+            LevelError::Ron(e) => write!(f, "couldn't parse scene: {}", e),
+            LevelError::Malformed(e) => write!(f, "unexpected scene layout: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl SceneEntity {
+    pub fn has<T>(&self) -> bool {
+        self.components
+            .iter()
+            .any(|component| component.type_name == type_name::<T>())
+    }
+
+    pub fn grid_location(&self) -> Option<GridLocation> {
+        let component = self
+            .components
+            .iter()
+            .find(|component| component.type_name == type_name::<GridLocation>())?;
+
+        Some(GridLocation {
+            x: component.int_field("x")? as i32,
+            y: component.int_field("y")? as i32,
+        })
+    }
+
+    pub fn segment_index(&self) -> Option<u32> {
+        let component = self
+            .components
+            .iter()
+            .find(|component| component.type_name == type_name::<SnakeSegment>())?;
+
+        Some(component.int_field("index")? as u32)
+    }
+}
+
+impl SceneComponent {
+    fn int_field(&self, name: &str) -> Option<i64> {
+        let (_name, value) = self.fields.iter().find(|(n, _value)| n == name)?;
+        match value {
+            Value::Number(Number::Integer(i)) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+impl Level {
+    pub fn load(path: &Path) -> Result<Level, LevelError> {
+        Ok(Level::from_scene(&read_scene(path)?))
+    }
+
+    pub fn from_scene(entities: &[SceneEntity]) -> Level {
+        let mut grounds = vec![];
+        let mut snakes = vec![];
+        let mut foods = vec![];
+        let mut poisons = vec![];
+
+        for entity in entities.iter() {
+            let grid_location = match entity.grid_location() {
+                Some(grid_location) => grid_location,
+                None => continue,
+            };
+
+            if entity.has::<Ground>() {
+                grounds.push(grid_location);
+            } else if entity.has::<Snake>() {
+                snakes.push((grid_location.clone(), entity.segment_index(), grid_location));
+            } else if entity.has::<Food>() {
+                foods.push(grid_location);
+            } else if entity.has::<Poison>() {
+                poisons.push(grid_location);
+            }
+        }
+
+        Level {
+            board: Board::new(grounds.into_iter().collect()),
+            state: PuzzleState::new(snake_order(snakes), foods, poisons),
+        }
+    }
+}
+
+// the order the game builds SnakeParts in, head first.
+// numbered segments are sorted by index; older scenes without numbers
+// assume that the rightmost snake is first in the array.
+pub(crate) fn snake_order<T>(segments: Vec<(GridLocation, Option<u32>, T)>) -> Vec<T> {
+    if segments
+        .iter()
+        .all(|(_location, index, _t)| index.is_some())
+    {
+        let mut segments = segments;
+        segments.sort_by_key(|(_location, index, _t)| *index);
+        return segments
+            .into_iter()
+            .map(|(_location, _index, t)| t)
+            .collect();
+    }
+
+    let mut ordered = vec![];
+    let mut max_x = None;
+    for (grid_location, _index, t) in segments {
+        match max_x {
+            Some(max_x) => {
+                if grid_location.x > max_x {
+                    ordered.insert(0, t)
+                } else {
+                    ordered.push(t);
+                }
+            }
+            None => {
+                max_x = Some(grid_location.x);
+                ordered.push(t);
+            }
+        }
+    }
+    ordered
+}
+
+pub fn read_scene(path: &Path) -> Result<Vec<SceneEntity>, LevelError> {
+    let data = fs::read_to_string(path).map_err(LevelError::Io)?;
+    parse_scene(&data)
+}
+
+pub fn parse_scene(data: &str) -> Result<Vec<SceneEntity>, LevelError> {
+    let value: Value = ron::from_str(data).map_err(LevelError::Ron)?;
+
+    let entities = match value {
+        Value::Seq(entities) => entities,
+        _ => return Err(LevelError::Malformed("expected a list of entities".into())),
+    };
+
+    let mut scene = vec![];
+    for entity in entities.iter() {
+        let entity = as_map(entity, "entity")?;
+
+        let id = match get(entity, "entity") {
+            Some(Value::Number(Number::Integer(id))) => *id,
+            _ => return Err(LevelError::Malformed("entity without an id".into())),
+        };
+
+        let components = match get(entity, "components") {
+            Some(Value::Seq(components)) => components,
+            _ => {
+                return Err(LevelError::Malformed(format!(
+                    "entity {} has no components",
+                    id
+                )))
+            }
+        };
+
+        let mut parsed = vec![];
+        for component in components.iter() {
+            let component = as_map(component, "component")?;
+
+            let type_name = match get(component, "type") {
+                Some(Value::String(type_name)) => type_name.clone(),
+                _ => {
+                    return Err(LevelError::Malformed(format!(
+                        "entity {} has a component without a type",
+                        id
+                    )))
+                }
+            };
+
+            let mut fields = vec![];
+            if let Some(Value::Map(field_map)) = get(component, "struct") {
+                for (name, field) in field_map.iter() {
+                    let name = match name {
+                        Value::String(name) => name.clone(),
+                        _ => continue,
+                    };
+                    let value = match field {
+                        Value::Map(field) => get(field, "value").cloned().unwrap_or(Value::Unit),
+                        _ => Value::Unit,
+                    };
+                    fields.push((name, value));
+                }
+            }
+
+            parsed.push(SceneComponent { type_name, fields });
+        }
+
+        scene.push(SceneEntity {
+            entity: id,
+            components: parsed,
+        });
+    }
+
+    Ok(scene)
+}
+
+fn as_map<'a>(value: &'a Value, what: &str) -> Result<&'a Map, LevelError> {
+    match value {
+        Value::Map(map) => Ok(map),
+        _ => Err(LevelError::Malformed(format!(
+            "expected {} to be a map",
+            what
+        ))),
+    }
+}
+
+fn get<'a>(map: &'a Map, key: &str) -> Option<&'a Value> {
+    map.iter().find_map(|(k, v)| match k {
+        Value::String(k) if k == key => Some(v),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{parse_scene, Level};
+    use crate::GridLocation;
+
+    // a scene entity at `x`, `y` with the marker component `type_name`, and a
+    // SnakeSegment if it has an index
+    pub(crate) fn entity(id: i64, type_name: &str, x: i32, y: i32, index: Option<u32>) -> String {
+        let segment = match index {
+            Some(index) => format!(
+                r#"
+      {{
+        "type": "taileater::SnakeSegment",
+        "struct": {{
+          "index": {{ "type": "u32", "value": {}, }},
+        }},
+      }},"#,
+                index
+            ),
+            None => String::new(),
+        };
+        format!(
+            r#"(
+    entity: {},
+    components: [
+      {{
+        "type": "taileater::GridLocation",
+        "struct": {{
+          "x": {{ "type": "i32", "value": {}, }},
+          "y": {{ "type": "i32", "value": {}, }},
+        }},
+      }},
+      {{ "type": "{}", "struct": {{}}, }},{}
+    ],
+  ),"#,
+            id, x, y, type_name, segment
+        )
+    }
+
+    #[test]
+    fn snake_follows_segment_indices() {
+        // a snake bent around a corner, head at the bottom
+        let scene = format!(
+            "[{}{}{}]",
+            entity(0, "taileater::Snake", 1, 1, Some(2)),
+            entity(1, "taileater::Snake", 0, 0, Some(0)),
+            entity(2, "taileater::Snake", 0, 1, Some(1)),
+        );
+        let level = Level::from_scene(&parse_scene(&scene).unwrap());
+
+        assert_eq!(
+            level.state.snake,
+            vec![
+                GridLocation { x: 0, y: 0 },
+                GridLocation { x: 0, y: 1 },
+                GridLocation { x: 1, y: 1 },
+            ]
+        );
+    }
+}
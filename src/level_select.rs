@@ -0,0 +1,795 @@
+use bevy::prelude::*;
+
+use crate::controls::{Action, Actions};
+use crate::manifest::{LevelEntry, Packs};
+use crate::par;
+use crate::save::{BeatLevels, Stats};
+use crate::{AudioCue, GameState, GridLocation, LevelId, Selected};
+
+pub struct LevelSelectPlugin;
+
+impl Plugin for LevelSelectPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(CurrentPage(0))
+            .add_system_set(
+                SystemSet::on_enter(GameState::LevelSelect)
+                    .with_system(open_selected_page.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::LevelSelect).with_system(draw_page.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::LevelSelect).with_system(enter_level.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::LevelSelect).with_system(update_selected.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::LevelSelect).with_system(tap_level.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::LevelSelect).with_system(display_selected.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::LevelSelect)
+                    .with_system(display_selected_info.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::LevelSelect).with_system(exit_levelselect.system()),
+            );
+    }
+}
+
+const LEVELS_PER_ROW: usize = 4;
+// room for the tail or head either side of a row of levels
+const SLOTS_PER_ROW: usize = LEVELS_PER_ROW + 2;
+const ROWS_PER_PAGE: usize = 5;
+const LEVELS_PER_PAGE: usize = LEVELS_PER_ROW * ROWS_PER_PAGE;
+// the page is laid out on a 21 x 21 grid
+const UNIT: f32 = 100. / 21.;
+
+// one screen of the level select, holding up to a page's worth of one pack's levels
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Page {
+    pack: usize,
+    first: usize,
+    count: usize,
+}
+
+impl Page {
+    fn levels<'a>(&self, packs: &'a Packs) -> &'a [LevelEntry] {
+        &packs.0[self.pack].manifest.levels[self.first..self.first + self.count]
+    }
+}
+
+// packs in order, each split over as many pages as it needs
+fn pages(packs: &Packs) -> Vec<Page> {
+    let mut pages = vec![];
+    for (pack, contents) in packs.0.iter().enumerate() {
+        let len = contents.manifest.levels.len();
+        for first in (0..len).step_by(LEVELS_PER_PAGE) {
+            pages.push(Page {
+                pack,
+                first,
+                count: (len - first).min(LEVELS_PER_PAGE),
+            });
+        }
+    }
+    pages
+}
+
+struct CurrentPage(usize);
+
+struct PageRoot;
+
+struct SelectedInfo;
+
+// the image behind a level's number, which can be tapped
+struct LevelTile(GridLocation);
+
+// where the snake puts a page's `index`th level; odd rows run right to left
+fn level_location(index: usize) -> GridLocation {
+    let row = index / LEVELS_PER_ROW;
+    let column = index % LEVELS_PER_ROW;
+    let x = if row % 2 == 0 {
+        column
+    } else {
+        LEVELS_PER_ROW - 1 - column
+    };
+    GridLocation {
+        x: x as i32,
+        y: row as i32,
+    }
+}
+
+fn level_index(location: &GridLocation, count: usize) -> Option<usize> {
+    if location.x < 0 || location.x >= LEVELS_PER_ROW as i32 || location.y < 0 {
+        return None;
+    }
+
+    let row = location.y as usize;
+    let x = location.x as usize;
+    let column = if row % 2 == 0 {
+        x
+    } else {
+        LEVELS_PER_ROW - 1 - x
+    };
+    let index = row * LEVELS_PER_ROW + column;
+    if index < count {
+        Some(index)
+    } else {
+        None
+    }
+}
+
+fn select_level(packs: &Packs, page: &Page, index: usize) -> Selected {
+    Selected(level_location(index), page.levels(packs)[index].id.clone())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathTile {
+    Tail,
+    Head { facing_left: bool },
+    Level { index: usize, tile: String },
+}
+
+impl PathTile {
+    fn asset_path(&self) -> String {
+        let tile = match self {
+            PathTile::Tail => "tail",
+            PathTile::Head { facing_left: false } => "head",
+            PathTile::Head { facing_left: true } => "head_left",
+            PathTile::Level { tile, .. } => tile.as_str(),
+        };
+        format!("sprites/drafts/level_select/{}.png", tile)
+    }
+}
+
+// Lays a snake through `count` levels: the tail starts top left, rows run
+// alternately right and left, turning down at the ends, and the head sits just
+// past the last level. Body tiles are named for the sides they join, from the
+// tail end to the head end, and shaded light and dark in turn.
+fn snake_path(count: usize) -> Vec<Vec<Option<PathTile>>> {
+    let rows = (count + LEVELS_PER_ROW - 1) / LEVELS_PER_ROW;
+    let mut path = vec![vec![None; SLOTS_PER_ROW]; rows];
+
+    if count > 0 {
+        path[0][0] = Some(PathTile::Tail);
+    }
+
+    for index in 0..count {
+        let row = index / LEVELS_PER_ROW;
+        let column = index % LEVELS_PER_ROW;
+        let rightwards = row % 2 == 0;
+
+        let slot = level_location(index).x as usize + 1;
+        let (behind, ahead) = if rightwards { ('l', 'r') } else { ('r', 'l') };
+        let last = index + 1 == count;
+
+        let from = if row > 0 && column == 0 { 'u' } else { behind };
+        let to = if !last && column == LEVELS_PER_ROW - 1 {
+            'd'
+        } else {
+            ahead
+        };
+        let shade = if index % 2 == 0 { "light" } else { "dark" };
+
+        path[row][slot] = Some(PathTile::Level {
+            index,
+            tile: format!("body_{}{}_{}", from, to, shade),
+        });
+
+        if last {
+            let head = if rightwards { slot + 1 } else { slot - 1 };
+            path[row][head] = Some(PathTile::Head {
+                facing_left: !rightwards,
+            });
+        }
+    }
+
+    path
+}
+
+// back to the page holding the last level played
+fn open_selected_page(
+    packs: Res<Packs>,
+    mut current_page: ResMut<CurrentPage>,
+    mut selected: ResMut<Selected>,
+) {
+    let pages = pages(&packs);
+    if pages.is_empty() {
+        return;
+    }
+
+    let (number, index) = pages
+        .iter()
+        .enumerate()
+        .find_map(|(number, page)| {
+            page.levels(&packs)
+                .iter()
+                .position(|level| level.id == selected.1)
+                .map(|index| (number, index))
+        })
+        .unwrap_or((0, 0));
+
+    *current_page = CurrentPage(number);
+    *selected = select_level(&packs, &pages[number], index);
+}
+
+fn draw_page(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+
+    packs: Res<Packs>,
+    current_page: Res<CurrentPage>,
+    selected: Res<Selected>,
+    beat_levels: Res<BeatLevels>,
+    stats: Res<Stats>,
+
+    roots: Query<Entity, With<PageRoot>>,
+) {
+    if !current_page.is_changed() {
+        return;
+    }
+
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+
+    let pages = pages(&packs);
+    let page = match pages.get(current_page.0) {
+        Some(page) => *page,
+        None => return,
+    };
+    let levels = page.levels(&packs);
+    let path = snake_path(levels.len());
+
+    // e.g. "< DRAFTS 2/2", with arrows pointing at the neighbouring pages
+    let pack_pages = pages.iter().filter(|other| other.pack == page.pack).count();
+    let heading = format!(
+        "{}{} {}/{}{}",
+        if current_page.0 > 0 { "< " } else { "" },
+        packs.0[page.pack].manifest.name,
+        page.first / LEVELS_PER_PAGE + 1,
+        pack_pages,
+        if current_page.0 + 1 < pages.len() {
+            " >"
+        } else {
+            ""
+        },
+    );
+
+    let background = materials.add(Color::rgb(0.15, 0.15, 0.15).into());
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                justify_content: JustifyContent::Center,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(PageRoot)
+        .with_children(|root| {
+            root.spawn_bundle(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(2. * UNIT)),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                material: background.clone(),
+                ..Default::default()
+            })
+            .with_children(|row| {
+                row.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        heading,
+                        TextStyle {
+                            font: asset_server.load("fonts/AsepriteFont.ttf"),
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                        TextAlignment {
+                            vertical: VerticalAlign::Center,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    ),
+                    ..Default::default()
+                });
+            });
+
+            root.spawn_bundle(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(UNIT)),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                material: background.clone(),
+                ..Default::default()
+            })
+            .with_children(|row| {
+                row.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "PICK A LEVEL",
+                        TextStyle {
+                            font: asset_server.load("fonts/AsepriteFont.ttf"),
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                        TextAlignment {
+                            vertical: VerticalAlign::Center,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    ),
+                    ..Default::default()
+                });
+            });
+
+            root.spawn_bundle(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(UNIT)),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                material: background.clone(),
+                ..Default::default()
+            });
+
+            for slots in path.iter() {
+                root.spawn_bundle(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::Center,
+                        size: Size::new(Val::Percent(100.0), Val::Percent(3. * UNIT)),
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    material: background.clone(),
+                    ..Default::default()
+                })
+                .with_children(|row| {
+                    spacer(row, &background, 1.5 * UNIT);
+
+                    for tile in slots.iter() {
+                        let tile = match tile {
+                            Some(tile) => tile,
+                            None => {
+                                spacer(row, &background, 3. * UNIT);
+                                continue;
+                            }
+                        };
+
+                        let image = tile_image(
+                            &mut materials,
+                            &asset_server,
+                            &tile.asset_path(),
+                            3. * UNIT,
+                        );
+
+                        if let PathTile::Level { index, .. } = tile {
+                            let entry = &levels[*index];
+                            let text = level_text(
+                                &asset_server,
+                                &beat_levels,
+                                &stats,
+                                entry,
+                                page.first + index,
+                            );
+                            level(row, image, text, entry.id.clone(), level_location(*index));
+                        } else {
+                            row.spawn_bundle(image);
+                        }
+                    }
+
+                    spacer(row, &background, 1.5 * UNIT);
+                });
+            }
+
+            // empty rows keep a short page the same size
+            for _ in path.len()..ROWS_PER_PAGE {
+                root.spawn_bundle(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::Center,
+                        size: Size::new(Val::Percent(100.0), Val::Percent(3. * UNIT)),
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    material: background.clone(),
+                    ..Default::default()
+                });
+            }
+
+            root.spawn_bundle(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(2. * UNIT)),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                material: background.clone(),
+                ..Default::default()
+            })
+            .with_children(|row| {
+                row.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        selected_info(&packs, &selected.1),
+                        TextStyle {
+                            font: asset_server.load("fonts/AsepriteFont.ttf"),
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                        },
+                        TextAlignment {
+                            vertical: VerticalAlign::Center,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(SelectedInfo);
+            });
+        });
+}
+
+fn spacer(row: &mut ChildBuilder, background: &Handle<ColorMaterial>, width: f32) {
+    row.spawn_bundle(NodeBundle {
+        style: Style {
+            justify_content: JustifyContent::Center,
+            size: Size::new(Val::Percent(width), Val::Percent(100.0)),
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        material: background.clone(),
+        ..Default::default()
+    });
+}
+
+fn tile_image(
+    materials: &mut Assets<ColorMaterial>,
+    asset_server: &AssetServer,
+    asset_path: &str,
+    width: f32,
+) -> ImageBundle {
+    ImageBundle {
+        style: Style {
+            justify_content: JustifyContent::Center,
+            size: Size::new(Val::Percent(width), Val::Percent(100.0)),
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        material: materials.add(asset_server.load(asset_path).into()),
+        ..Default::default()
+    }
+}
+
+fn level_text(
+    asset_server: &AssetServer,
+    beat_levels: &BeatLevels,
+    stats: &Stats,
+    entry: &LevelEntry,
+    number: usize,
+) -> Text {
+    let color = {
+        if beat_levels.0.contains(&entry.id) {
+            Color::GRAY
+        } else {
+            Color::WHITE
+        }
+    };
+
+    let mut text = Text::with_section(
+        format!("{}", number),
+        TextStyle {
+            font: asset_server.load("fonts/AsepriteFont.ttf"),
+            font_size: 32.0,
+            color,
+        },
+        TextAlignment {
+            vertical: VerticalAlign::Center,
+            horizontal: HorizontalAlign::Center,
+        },
+    );
+
+    // best rating so far, under the level number
+    let best_moves = stats
+        .0
+        .get(&entry.id)
+        .and_then(|level_stats| level_stats.best_moves);
+    if let (Some(best_moves), Some(par)) = (best_moves, entry.par) {
+        text.sections.push(TextSection {
+            value: format!("\n{}", par::star_text(par::stars(best_moves, par))),
+            style: TextStyle {
+                font: asset_server.load("fonts/AsepriteFont.ttf"),
+                font_size: 16.0,
+                color,
+            },
+        });
+    }
+
+    text
+}
+
+fn level(
+    row: &mut ChildBuilder,
+    image: ImageBundle,
+    text: Text,
+    level_id: LevelId,
+    grid_location: GridLocation,
+) {
+    row.spawn_bundle(image)
+        .insert(Interaction::default())
+        .insert(LevelTile(grid_location.clone()))
+        .with_children(|image| {
+            image
+                .spawn_bundle(TextBundle {
+                    text,
+                    ..Default::default()
+                })
+                .insert(level_id)
+                .insert(grid_location);
+        });
+}
+
+fn update_selected(
+    actions: Res<Actions>,
+
+    packs: Res<Packs>,
+    mut current_page: ResMut<CurrentPage>,
+    mut selected: ResMut<Selected>,
+
+    mut audio_cues: EventWriter<AudioCue>,
+) {
+    let (dx, dy) = if actions.just_pressed(Action::Right) {
+        (1, 0)
+    } else if actions.just_pressed(Action::Left) {
+        (-1, 0)
+    } else if actions.just_pressed(Action::Up) {
+        (0, -1)
+    } else if actions.just_pressed(Action::Down) {
+        (0, 1)
+    } else {
+        return;
+    };
+
+    let pages = pages(&packs);
+    let page = match pages.get(current_page.0) {
+        Some(page) => page,
+        None => return,
+    };
+    let target = GridLocation {
+        x: selected.0.x + dx,
+        y: selected.0.y + dy,
+    };
+
+    // off the side of a page is the next page along, on the same row if it reaches that far
+    let (number, index) = if let Some(index) = level_index(&target, page.count) {
+        (current_page.0, index)
+    } else if target.x < 0 || target.x >= LEVELS_PER_ROW as i32 {
+        let number = current_page.0 as i32 + dx;
+        if number < 0 || number >= pages.len() as i32 {
+            return;
+        }
+        let number = number as usize;
+
+        let x = if dx > 0 { 0 } else { LEVELS_PER_ROW as i32 - 1 };
+        let count = pages[number].count;
+        let index = level_index(&GridLocation { x, y: target.y }, count).unwrap_or(count - 1);
+        (number, index)
+    } else {
+        return;
+    };
+
+    audio_cues.send(AudioCue::ChangeSelection);
+    *selected = select_level(&packs, &pages[number], index);
+    if number != current_page.0 {
+        *current_page = CurrentPage(number);
+    }
+}
+
+// tapping a level selects it, and tapping it again plays it
+fn tap_level(
+    mut state: ResMut<State<GameState>>,
+
+    packs: Res<Packs>,
+    current_page: Res<CurrentPage>,
+    mut selected: ResMut<Selected>,
+
+    tiles: Query<(&Interaction, &LevelTile), Changed<Interaction>>,
+
+    mut audio_cues: EventWriter<AudioCue>,
+) {
+    for (interaction, LevelTile(location)) in tiles.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        if *location == selected.0 {
+            audio_cues.send(AudioCue::Enter);
+            state.set(GameState::InGame).ok();
+        } else if let Some(page) = pages(&packs).get(current_page.0) {
+            if let Some(index) = level_index(location, page.count) {
+                audio_cues.send(AudioCue::ChangeSelection);
+                *selected = select_level(&packs, page, index);
+            }
+        }
+    }
+}
+
+fn selected_info(packs: &Packs, level_id: &LevelId) -> String {
+    match packs.find(level_id) {
+        Some((_, level)) if level.author.is_empty() => level.title.clone(),
+        Some((_, level)) => format!("{} BY {}", level.title, level.author.to_uppercase()),
+        None => String::new(),
+    }
+}
+
+fn display_selected_info(
+    packs: Res<Packs>,
+    selected: Res<Selected>,
+    mut q: Query<&mut Text, With<SelectedInfo>>,
+) {
+    if !selected.is_changed() {
+        return;
+    }
+
+    for mut text in q.iter_mut() {
+        if let Some(text_section) = text.sections.first_mut() {
+            text_section.value = selected_info(&packs, &selected.1);
+        }
+    }
+}
+
+fn display_selected(selected: Res<Selected>, mut q: Query<(&GridLocation, &mut Text)>) {
+    let _count = 0;
+
+    for (grid_location, mut text) in q.iter_mut() {
+        if *grid_location == selected.0 {
+            if let Some(text_section) = text.sections.first_mut() {
+                text_section.style.font_size = 64.0
+            }
+        } else if let Some(text_section) = text.sections.first_mut() {
+            text_section.style.font_size = 32.0
+        }
+    }
+}
+
+fn enter_level(
+    mut state: ResMut<State<GameState>>,
+
+    selected: Res<Selected>,
+    mut actions: ResMut<Actions>,
+
+    q: Query<(&GridLocation, &LevelId)>,
+
+    mut audio_cues: EventWriter<AudioCue>,
+) {
+    if actions.just_pressed(Action::Back) {
+        actions.clear();
+        state.set(GameState::StartScreen).ok();
+        return;
+    }
+
+    if actions.just_pressed(Action::Confirm) {
+        audio_cues.send(AudioCue::Enter);
+
+        for (grid_location, level_id) in q.iter() {
+            if selected.0 == *grid_location {
+                dbg!("Entering level: {}", level_id.0);
+
+                state.set(GameState::InGame).ok();
+            }
+        }
+    }
+}
+
+fn exit_levelselect(mut commands: Commands, q: Query<(&Node, Entity)>) {
+    for (_node, e) in q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{level_index, level_location, pages, snake_path, Page, PathTile};
+    use crate::manifest::Packs;
+
+    fn tiles(row: &[Option<PathTile>]) -> Vec<String> {
+        row.iter()
+            .map(|tile| match tile {
+                Some(PathTile::Tail) => "tail".to_string(),
+                Some(PathTile::Head { facing_left: false }) => "head".to_string(),
+                Some(PathTile::Head { facing_left: true }) => "head_left".to_string(),
+                Some(PathTile::Level { index, tile }) => format!("{} {}", index, tile),
+                None => "".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn snakes_through_every_level() {
+        let path = snake_path(20);
+        assert_eq!(path.len(), 5);
+        assert_eq!(
+            tiles(&path[0]),
+            vec![
+                "tail",
+                "0 body_lr_light",
+                "1 body_lr_dark",
+                "2 body_lr_light",
+                "3 body_ld_dark",
+                ""
+            ]
+        );
+        assert_eq!(
+            tiles(&path[1]),
+            vec![
+                "",
+                "7 body_rd_dark",
+                "6 body_rl_light",
+                "5 body_rl_dark",
+                "4 body_ul_light",
+                ""
+            ]
+        );
+        assert_eq!(
+            tiles(&path[4]),
+            vec![
+                "",
+                "16 body_ur_light",
+                "17 body_lr_dark",
+                "18 body_lr_light",
+                "19 body_lr_dark",
+                "head"
+            ]
+        );
+
+        let path = snake_path(6);
+        assert_eq!(
+            tiles(&path[1]),
+            vec!["", "", "head_left", "5 body_rl_dark", "4 body_ul_light", ""]
+        );
+
+        // every length only needs sprites that exist
+        for count in 1..=40 {
+            for tile in snake_path(count).into_iter().flatten().flatten() {
+                let path = format!("assets/{}", tile.asset_path());
+                assert!(std::path::Path::new(&path).exists(), "{} is missing", path);
+            }
+        }
+    }
+
+    #[test]
+    fn packs_split_into_pages() {
+        let pages = pages(&Packs::bundled());
+        assert_eq!(
+            pages,
+            vec![
+                Page {
+                    pack: 0,
+                    first: 0,
+                    count: 20
+                },
+                Page {
+                    pack: 1,
+                    first: 0,
+                    count: 20
+                },
+                Page {
+                    pack: 1,
+                    first: 20,
+                    count: 5
+                },
+            ]
+        );
+
+        for index in 0..20 {
+            assert_eq!(level_index(&level_location(index), 20), Some(index));
+        }
+        assert_eq!(level_index(&level_location(7), 6), None);
+    }
+}
@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use history::History;
+
+mod audio;
+mod controls;
+mod edit_history;
+mod edit_tools;
+mod editor;
+mod game;
+pub mod history;
+mod input_buffer;
+pub mod level;
+mod level_select;
+pub mod lint;
+pub mod manifest;
+pub mod par;
+mod playback;
+mod rebind;
+pub mod replay;
+pub mod rules;
+mod save;
+pub mod solver;
+mod stats;
+mod title;
+mod touch;
+mod tween;
+
+pub use audio::{AudioCue, AudioFeedbackPlugin};
+pub use editor::{EditorFile, TaileaterEditorPlugin};
+pub use game::TaileaterGamePlugin;
+pub use input_buffer::InputBuffer;
+pub use level_select::LevelSelectPlugin;
+pub use playback::Playback;
+pub use tween::{Easing, MoveAnimation};
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct InputLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct BufferLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct HistoryLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct FoodLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct PoisonLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct SnakeMovementLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct SpriteLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct TransformLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct GravityLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct WinLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct SetupLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct CleanupLabel;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Reflect, Default)]
+#[reflect(Component)]
+pub struct GridLocation {
+    pub x: i32,
+    pub y: i32,
+}
+
+struct LocationQueue(Vec<GridLocation>);
+
+impl std::ops::Add for GridLocation {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+#[derive(Reflect, Default)]
+#[reflect(Component)]
+pub struct Snake;
+
+// position of a snake segment counting from the head.
+// scenes saved before this existed fall back to guessing the order.
+#[derive(Debug, Clone, Reflect, Default)]
+#[reflect(Component)]
+pub struct SnakeSegment {
+    pub index: u32,
+}
+
+#[derive(Reflect, Default)]
+#[reflect(Component)]
+pub struct Ground;
+
+#[derive(Reflect, Default)]
+#[reflect(Component)]
+pub struct Food;
+
+#[derive(Reflect, Default)]
+#[reflect(Component)]
+pub struct Poison;
+
+struct SnakeParts(Vec<Entity>);
+
+struct MaybeSnakeAssets(Option<SnakeAssets>);
+struct SnakeAssets {
+    head: Handle<TextureAtlas>,
+    tail: Handle<TextureAtlas>,
+    light_body: Handle<TextureAtlas>,
+    dark_body: Handle<TextureAtlas>,
+    glowing_body: Handle<TextureAtlas>,
+    head_to_orb: Handle<TextureAtlas>,
+    poison: Handle<ColorMaterial>,
+    food: Handle<ColorMaterial>,
+    ground: Handle<ColorMaterial>,
+    wall: Handle<ColorMaterial>,
+}
+
+const GRID_WIDTH: f32 = 32.0;
+const GRID_HEIGHT: f32 = 32.0;
+
+// the board is every cell within this many of the origin; 21x21 fills the window
+const BOARD_RADIUS: i32 = 10;
+
+struct MainCamera;
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug, Copy)]
+struct Orientation {
+    from: Direction,
+    to: Direction,
+}
+
+#[derive(Clone, Debug)]
+struct Transition {
+    from: Orientation,
+    to: Orientation,
+    index: u32,
+}
+
+#[derive(Clone, Debug)]
+struct TransitionQueue(Vec<Transition>);
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum GameState {
+    Szunami,
+    StartScreen,
+    LevelSelect,
+    InGame,
+    Win,
+    // only the editor app starts here; playtests run InGame from it
+    Editor,
+    // the editor's save dialog, pushed over Editor
+    Naming,
+    // rebinding keys, from the title
+    Controls,
+}
+
+// what the player asked for this frame; controls and replays both send these
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerInput {
+    Move(Direction),
+    Undo,
+    Redo,
+    SwitchBranch,
+    Reset,
+}
+
+// the input let out of the InputBuffer this frame, once the snake is at rest
+struct BufferedInput(PlayerInput);
+
+// inputs that actually changed the game, for recording replays
+struct AcceptedInput(PlayerInput);
+
+#[derive(Debug)]
+struct Snapshot {
+    // in order of snakeparts!
+    snakes: Vec<(GridLocation, Transition)>,
+    foods: Vec<GridLocation>,
+    poisons: Vec<GridLocation>,
+}
+// empty until the level has finished spawning
+struct GameHistory(Option<History<Snapshot>>);
+
+#[derive(Clone, Debug)]
+struct Selected(GridLocation, LevelId);
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub struct LevelId(pub usize);
+
+// the types that make up a level; scenes refer to them by type path,
+// so they have to live at the crate root.
+fn register_level_types(app: &mut AppBuilder) {
+    app.register_type::<Ground>()
+        .register_type::<GridLocation>()
+        .register_type::<Snake>()
+        .register_type::<SnakeSegment>()
+        .register_type::<Food>()
+        .register_type::<Poison>();
+}
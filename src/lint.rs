@@ -0,0 +1,315 @@
+// Static checks for level scenes, so broken levels are caught before they are played.
+
+use std::any::type_name;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::level::{Level, SceneEntity};
+use crate::rules;
+use crate::{Food, GridLocation, Ground, Poison, Snake, SnakeSegment, BOARD_RADIUS};
+
+// the area drawn by `wall()`
+const MIN_COORD: i32 = -BOARD_RADIUS;
+const MAX_COORD: i32 = BOARD_RADIUS;
+
+#[derive(Clone, Debug)]
+pub enum Problem {
+    UnknownType {
+        entity: i64,
+        type_name: String,
+    },
+    MissingLocation {
+        entity: i64,
+    },
+    OutOfBounds {
+        entity: i64,
+        location: GridLocation,
+    },
+    Overlap {
+        location: GridLocation,
+        entities: Vec<i64>,
+    },
+    DuplicateSegment {
+        index: u32,
+    },
+    UnnumberedSegments,
+    Disconnected {
+        from: GridLocation,
+        to: GridLocation,
+    },
+    TooShort {
+        len: usize,
+        foods: usize,
+    },
+    FallsOnLoad,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::UnknownType { entity, type_name } => {
+                write!(f, "entity {} has unknown component {}", entity, type_name)
+            }
+            Problem::MissingLocation { entity } => {
+                write!(f, "entity {} has no GridLocation", entity)
+            }
+            Problem::OutOfBounds { entity, location } => write!(
+                f,
+                "entity {} at {} is outside the level",
+                entity,
+                coords(location)
+            ),
+            Problem::Overlap { location, entities } => {
+                write!(f, "entities {:?} overlap at {}", entities, coords(location))
+            }
+            Problem::DuplicateSegment { index } => {
+                write!(f, "more than one snake segment has index {}", index)
+            }
+            Problem::UnnumberedSegments => {
+                write!(f, "only some snake segments have a SnakeSegment index")
+            }
+            Problem::Disconnected { from, to } => write!(
+                f,
+                "snake segments at {} and {} are not connected",
+                coords(from),
+                coords(to)
+            ),
+            Problem::TooShort { len, foods } => write!(
+                f,
+                "snake of length {} with {} food can never eat its tail",
+                len, foods
+            ),
+            Problem::FallsOnLoad => write!(f, "snake falls out of the level on load"),
+        }
+    }
+}
+
+fn coords(location: &GridLocation) -> String {
+    format!("({}, {})", location.x, location.y)
+}
+
+pub fn lint(entities: &[SceneEntity]) -> Vec<Problem> {
+    let known = [
+        type_name::<GridLocation>(),
+        type_name::<Snake>(),
+        type_name::<SnakeSegment>(),
+        type_name::<Ground>(),
+        type_name::<Food>(),
+        type_name::<Poison>(),
+    ];
+
+    let mut problems = vec![];
+    let mut occupied: HashMap<GridLocation, Vec<i64>> = HashMap::new();
+
+    for entity in entities.iter() {
+        for component in entity.components.iter() {
+            if !known.contains(&component.type_name.as_str()) {
+                problems.push(Problem::UnknownType {
+                    entity: entity.entity,
+                    type_name: component.type_name.clone(),
+                });
+            }
+        }
+
+        let location = match entity.grid_location() {
+            Some(location) => location,
+            None => {
+                problems.push(Problem::MissingLocation {
+                    entity: entity.entity,
+                });
+                continue;
+            }
+        };
+
+        if location.x < MIN_COORD
+            || location.x > MAX_COORD
+            || location.y < MIN_COORD
+            || location.y > MAX_COORD
+        {
+            problems.push(Problem::OutOfBounds {
+                entity: entity.entity,
+                location: location.clone(),
+            });
+        }
+
+        occupied.entry(location).or_default().push(entity.entity);
+    }
+
+    let mut overlaps: Vec<_> = occupied
+        .into_iter()
+        .filter(|(_location, entities)| entities.len() > 1)
+        .collect();
+    overlaps.sort_by_key(|(location, _entities)| (location.x, location.y));
+    for (location, entities) in overlaps {
+        problems.push(Problem::Overlap { location, entities });
+    }
+
+    let indices: Vec<Option<u32>> = entities
+        .iter()
+        .filter(|entity| entity.has::<Snake>())
+        .map(|entity| entity.segment_index())
+        .collect();
+    if indices.iter().any(|index| index.is_some()) && indices.iter().any(|index| index.is_none()) {
+        problems.push(Problem::UnnumberedSegments);
+    }
+    let mut numbered: Vec<u32> = indices.into_iter().flatten().collect();
+    numbered.sort_unstable();
+    for pair in numbered.windows(2) {
+        if pair[0] == pair[1] {
+            problems.push(Problem::DuplicateSegment { index: pair[0] });
+        }
+    }
+
+    let level = Level::from_scene(entities);
+    let snake = &level.state.snake;
+
+    for pair in snake.windows(2) {
+        let distance = (pair[0].x - pair[1].x).abs() + (pair[0].y - pair[1].y).abs();
+        if distance != 1 {
+            problems.push(Problem::Disconnected {
+                from: pair[0].clone(),
+                to: pair[1].clone(),
+            });
+        }
+    }
+
+    // every food grows the snake by one; it needs at least three segments to win
+    let foods = level.state.foods.len();
+    if snake.len() + foods <= 2 {
+        problems.push(Problem::TooShort {
+            len: snake.len(),
+            foods,
+        });
+    }
+
+    let mut settled = level.state.clone();
+    settled.settle(&level.board);
+    if !snake.is_empty() && rules::has_fallen(&settled.snake) {
+        problems.push(Problem::FallsOnLoad);
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{lint, Problem};
+    use crate::level::tests::entity;
+    use crate::level::{parse_scene, read_scene};
+    use crate::manifest::Packs;
+
+    fn problems(entities: &[String]) -> Vec<Problem> {
+        lint(&parse_scene(&format!("[{}]", entities.concat())).unwrap())
+    }
+
+    // a snake on the ground, long enough to win
+    fn snake() -> Vec<String> {
+        vec![
+            entity(0, "taileater::Snake", 0, 0, None),
+            entity(1, "taileater::Snake", 1, 0, None),
+            entity(2, "taileater::Snake", 2, 0, None),
+            entity(3, "taileater::Ground", 0, -1, None),
+        ]
+    }
+
+    #[test]
+    fn a_sound_level_has_no_problems() {
+        assert!(problems(&snake()).is_empty());
+    }
+
+    #[test]
+    fn reports_unknown_components() {
+        let mut scene = snake();
+        scene.push(entity(4, "taileater::Lava", 3, 3, None));
+        assert!(problems(&scene)
+            .iter()
+            .any(|p| matches!(p, Problem::UnknownType { entity: 4, .. })));
+    }
+
+    #[test]
+    fn reports_entities_outside_the_walls() {
+        let mut scene = snake();
+        scene.push(entity(4, "taileater::Food", 11, 0, None));
+        assert!(problems(&scene)
+            .iter()
+            .any(|p| matches!(p, Problem::OutOfBounds { entity: 4, .. })));
+    }
+
+    #[test]
+    fn reports_overlaps() {
+        let mut scene = snake();
+        scene.push(entity(4, "taileater::Food", 0, 0, None));
+        assert!(problems(&scene)
+            .iter()
+            .any(|p| matches!(p, Problem::Overlap { entities, .. } if entities == &vec![0, 4])));
+    }
+
+    #[test]
+    fn reports_duplicate_segment_indices() {
+        let scene = [
+            entity(0, "taileater::Snake", 0, 0, Some(0)),
+            entity(1, "taileater::Snake", 1, 0, Some(0)),
+            entity(2, "taileater::Snake", 2, 0, Some(1)),
+        ];
+        assert!(problems(&scene)
+            .iter()
+            .any(|p| matches!(p, Problem::DuplicateSegment { index: 0 })));
+    }
+
+    #[test]
+    fn reports_partly_numbered_snakes() {
+        let scene = [
+            entity(0, "taileater::Snake", 0, 0, Some(0)),
+            entity(1, "taileater::Snake", 1, 0, None),
+            entity(2, "taileater::Snake", 2, 0, Some(1)),
+        ];
+        assert!(problems(&scene)
+            .iter()
+            .any(|p| matches!(p, Problem::UnnumberedSegments)));
+    }
+
+    #[test]
+    fn reports_disconnected_snakes() {
+        let mut scene = snake();
+        scene[2] = entity(2, "taileater::Snake", 3, 0, None);
+        assert!(problems(&scene)
+            .iter()
+            .any(|p| matches!(p, Problem::Disconnected { .. })));
+    }
+
+    #[test]
+    fn reports_snakes_too_short_to_win() {
+        let mut scene = snake();
+        scene.remove(2);
+        assert!(problems(&scene)
+            .iter()
+            .any(|p| matches!(p, Problem::TooShort { len: 2, foods: 0 })));
+
+        scene.push(entity(4, "taileater::Food", 3, 3, None));
+        assert!(!problems(&scene)
+            .iter()
+            .any(|p| matches!(p, Problem::TooShort { .. })));
+    }
+
+    #[test]
+    fn reports_snakes_that_fall_on_load() {
+        let mut scene = snake();
+        scene.pop();
+        assert!(problems(&scene)
+            .iter()
+            .any(|p| matches!(p, Problem::FallsOnLoad)));
+    }
+
+    #[test]
+    fn bundled_levels_have_no_problems() {
+        for pack in Packs::bundled().0.iter() {
+            for level in pack.manifest.levels.iter() {
+                let path = format!("assets/{}", pack.scene_path(level));
+                let problems = lint(&read_scene(Path::new(&path)).unwrap());
+                assert!(problems.is_empty(), "{}: {:?}", path, problems);
+            }
+        }
+    }
+}
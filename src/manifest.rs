@@ -0,0 +1,337 @@
+// Level packs: a directory of scenes plus a manifest listing them in play order.
+//
+// Scene files are named by the manifest, so levels can be added, reordered
+// or renamed without touching the level select. Level ids key the save data,
+// so they must stay unique across every pack.
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::LevelId;
+
+// where players keep their own levels, unless `--levels-dir` says otherwise
+pub const DEFAULT_LEVELS_DIR: &str = "levels";
+
+// custom levels have no manifest, so they're numbered from here each run
+pub const CUSTOM_IDS: usize = 1_000_000;
+
+pub struct LevelsDir(pub PathBuf);
+
+// baked in so the wasm build doesn't need to fetch them
+const BUNDLED: [(&str, &str); 2] = [
+    (
+        "scenes/prod",
+        include_str!("../assets/scenes/prod/manifest.ron"),
+    ),
+    (
+        "scenes/drafts",
+        include_str!("../assets/scenes/drafts/manifest.ron"),
+    ),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub levels: Vec<LevelEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelEntry {
+    pub id: LevelId,
+    pub title: String,
+    // relative to the manifest's directory
+    pub file: String,
+    pub author: String,
+    #[serde(default)]
+    pub par: Option<u32>,
+}
+
+impl Manifest {
+    pub fn from_ron(data: &str) -> Result<Manifest, ron::Error> {
+        ron::de::from_str(data)
+    }
+
+    // laid out like the bundled manifests, so promoting a level is a small diff
+    pub fn to_ron(&self, comment: &str) -> String {
+        let mut data = format!(
+            "#![enable(unwrap_newtypes)]\n{}(\n    name: {:?},\n    levels: [\n",
+            comment, self.name
+        );
+        for level in self.levels.iter() {
+            data += &format!(
+                "        (\n            id: {},\n            title: {:?},\n            file: {:?},\n            author: {:?},\n",
+                level.id.0, level.title, level.file, level.author
+            );
+            data += &match level.par {
+                Some(par) => format!("            par: Some({}),\n        ),\n", par),
+                None => "            par: None,\n        ),\n".to_string(),
+            };
+        }
+        data + "    ],\n)\n"
+    }
+}
+
+// a level name as a scene file name, or None if there's nothing to name it by
+pub fn scene_file_name(name: &str) -> Option<String> {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .collect();
+    if words.is_empty() {
+        None
+    } else {
+        Some(format!("{}.scn.ron", words.join("_")))
+    }
+}
+
+// copies a saved scene into the pack at `dir` under its next free id, and
+// lists it last in that pack's manifest; ids already used by `others` are
+// skipped. The bundled manifests are baked in, so the game needs a rebuild
+// to show it.
+pub fn promote(
+    scene: &Path,
+    dir: &Path,
+    title: String,
+    author: String,
+    par: Option<u32>,
+    others: &Packs,
+) -> io::Result<LevelEntry> {
+    let manifest_path = dir.join("manifest.ron");
+    let data = fs::read_to_string(&manifest_path)?;
+    let mut manifest = Manifest::from_ron(&data)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+    let taken = |id: usize| {
+        manifest.levels.iter().any(|level| level.id.0 == id) || others.find(&LevelId(id)).is_some()
+    };
+    let mut id = manifest
+        .levels
+        .iter()
+        .map(|level| level.id.0 + 1)
+        .max()
+        .unwrap_or(0);
+    while taken(id) {
+        id += 1;
+    }
+
+    let level = LevelEntry {
+        id: LevelId(id),
+        title,
+        file: format!("{}.scn.ron", id),
+        author,
+        par,
+    };
+    fs::copy(scene, dir.join(&level.file))?;
+
+    // keep the comment heading the manifest
+    let comment: String = data
+        .lines()
+        .take_while(|line| !line.starts_with('('))
+        .filter(|line| !line.starts_with("#!"))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    manifest.levels.push(level.clone());
+    fs::write(&manifest_path, manifest.to_ron(&comment))?;
+
+    Ok(level)
+}
+
+#[derive(Debug, Clone)]
+pub struct Pack {
+    // asset path of the directory holding the manifest and its scenes
+    pub dir: String,
+    pub manifest: Manifest,
+    // user levels, saved by file name rather than id
+    pub custom: bool,
+}
+
+impl Pack {
+    // every scene in a user levels directory, in file name order
+    pub fn scan(dir: &Path) -> io::Result<Pack> {
+        // the asset server takes absolute paths as they are
+        let dir = fs::canonicalize(dir)?;
+
+        let mut files = vec![];
+        for entry in fs::read_dir(&dir)? {
+            let file = entry?.file_name().to_string_lossy().to_string();
+            if file.ends_with(".scn.ron") {
+                files.push(file);
+            }
+        }
+        files.sort();
+
+        let levels = files
+            .into_iter()
+            .enumerate()
+            .map(|(i, file)| LevelEntry {
+                id: LevelId(CUSTOM_IDS + i),
+                title: file
+                    .trim_end_matches(".scn.ron")
+                    .replace('_', " ")
+                    .to_uppercase(),
+                file,
+                author: String::new(),
+                par: None,
+            })
+            .collect();
+
+        Ok(Pack {
+            dir: dir.to_string_lossy().to_string(),
+            manifest: Manifest {
+                name: "CUSTOM".to_string(),
+                levels,
+            },
+            custom: true,
+        })
+    }
+
+    pub fn scene_path(&self, level: &LevelEntry) -> String {
+        format!("{}/{}", self.dir, level.file)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Packs(pub Vec<Pack>);
+
+impl Packs {
+    pub fn bundled() -> Packs {
+        Packs(
+            BUNDLED
+                .iter()
+                .map(|(dir, manifest)| Pack {
+                    dir: dir.to_string(),
+                    manifest: Manifest::from_ron(manifest).expect("bundled manifest should parse"),
+                    custom: false,
+                })
+                .collect(),
+        )
+    }
+
+    // the user levels in `dir` as one more pack, if there are any
+    pub fn add_custom(&mut self, dir: &Path) -> io::Result<()> {
+        if !dir.exists() {
+            return Ok(());
+        }
+
+        let pack = Pack::scan(dir)?;
+        if !pack.manifest.levels.is_empty() {
+            self.0.push(pack);
+        }
+        Ok(())
+    }
+
+    pub fn find(&self, id: &LevelId) -> Option<(&Pack, &LevelEntry)> {
+        self.0.iter().find_map(|pack| {
+            pack.manifest
+                .levels
+                .iter()
+                .find(|level| level.id == *id)
+                .map(|level| (pack, level))
+        })
+    }
+
+    pub fn scene_path(&self, id: &LevelId) -> Option<String> {
+        self.find(id).map(|(pack, level)| pack.scene_path(level))
+    }
+
+    pub fn par(&self, id: &LevelId) -> Option<u32> {
+        self.find(id).and_then(|(_, level)| level.par)
+    }
+
+    // ids of this run's custom levels, with the file names they're saved under
+    pub fn custom_levels(&self) -> impl Iterator<Item = (&LevelId, &str)> {
+        self.0
+            .iter()
+            .filter(|pack| pack.custom)
+            .flat_map(|pack| pack.manifest.levels.iter())
+            .map(|level| (&level.id, level.file.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{promote, scene_file_name, Manifest, Pack, Packs, CUSTOM_IDS};
+    use crate::LevelId;
+
+    use std::collections::HashSet;
+    use std::fs;
+
+    #[test]
+    fn bundled_packs_list_every_scene_once() {
+        let packs = Packs::bundled();
+
+        assert_eq!(packs.0[0].manifest.levels.len(), 20);
+        assert_eq!(packs.par(&LevelId(6)), Some(4));
+
+        let mut ids = HashSet::new();
+        for pack in packs.0.iter() {
+            for level in pack.manifest.levels.iter() {
+                assert!(ids.insert(level.id.clone()), "{:?} is reused", level.id);
+
+                let path = format!("assets/{}", pack.scene_path(level));
+                assert!(std::path::Path::new(&path).exists(), "{} is missing", path);
+            }
+        }
+    }
+
+    #[test]
+    fn scans_custom_levels_in_name_order() {
+        let dir = std::env::temp_dir().join(format!("taileater_levels_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for file in ["zig_zag.scn.ron", "notes.txt", "a_start.scn.ron"].iter() {
+            fs::write(dir.join(file), "[]").unwrap();
+        }
+
+        let pack = Pack::scan(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let levels = &pack.manifest.levels;
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[0].file, "a_start.scn.ron");
+        assert_eq!(levels[0].title, "A START");
+        assert_eq!(levels[1].id, LevelId(CUSTOM_IDS + 1));
+        assert!(pack.custom);
+    }
+
+    #[test]
+    fn promotes_a_scene_under_the_next_free_id() {
+        assert_eq!(
+            scene_file_name(" Loop-de loop: 2 "),
+            Some("loop-de_loop_2.scn.ron".to_string())
+        );
+        assert_eq!(scene_file_name("::"), None);
+
+        let dir = std::env::temp_dir().join(format!("taileater_prod_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("manifest.ron"),
+            include_str!("../assets/scenes/prod/manifest.ron"),
+        )
+        .unwrap();
+        fs::write(dir.join("new.scn.ron"), "[]").unwrap();
+
+        let level = promote(
+            &dir.join("new.scn.ron"),
+            &dir,
+            "NEW".to_string(),
+            "someone".to_string(),
+            None,
+            &Packs::bundled(),
+        )
+        .unwrap();
+        let written = fs::read_to_string(dir.join("manifest.ron")).unwrap();
+        let copied = dir.join(&level.file).exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(level.id, LevelId(20));
+        assert!(copied);
+        assert!(written.starts_with("#![enable(unwrap_newtypes)]\n// levels in the order"));
+        let manifest = Manifest::from_ron(&written).unwrap();
+        assert_eq!(manifest.levels.len(), 21);
+        assert_eq!(manifest.levels[20].file, "20.scn.ron");
+    }
+}
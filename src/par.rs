@@ -0,0 +1,38 @@
+// Star ratings against a level's par move count.
+//
+// Pars live in the level manifest. They start as the shortest solutions found
+// by `taileater solve`; a designer can set a looser one by hand.
+
+pub const MAX_STARS: u8 = 3;
+
+// three stars at or under par, two within half as many moves again, one for finishing
+pub fn stars(moves: u32, par: u32) -> u8 {
+    if moves <= par {
+        3
+    } else if moves <= par + par / 2 {
+        2
+    } else {
+        1
+    }
+}
+
+// e.g. "**-" for two stars
+pub fn star_text(stars: u8) -> String {
+    (0..MAX_STARS)
+        .map(|star| if star < stars { '*' } else { '-' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{star_text, stars};
+
+    #[test]
+    fn rates_against_par() {
+        assert_eq!(stars(8, 10), 3);
+        assert_eq!(stars(10, 10), 3);
+        assert_eq!(stars(15, 10), 2);
+        assert_eq!(stars(16, 10), 1);
+        assert_eq!(star_text(2), "**-");
+    }
+}
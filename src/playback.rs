@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+
+use crate::controls::{Action, Actions};
+use crate::input_buffer::InputBuffer;
+use crate::replay::{Replay, ReplayStep};
+use crate::{
+    AcceptedInput, GameState, GridLocation, InputLabel, PlayerInput, Selected, SnakeMovementLabel,
+    SnakeParts,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const REPLAY_DIR: &str = "replays";
+
+// records every level played, and plays a replay back when one is inserted
+pub(crate) struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Szunami).with_system(start_playback.system()),
+        )
+        .add_system_set(
+            SystemSet::on_enter(GameState::InGame).with_system(start_recording.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(play_inputs.system())
+                .label(InputLabel),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(record.system())
+                .after(SnakeMovementLabel),
+        )
+        .add_system_set(SystemSet::on_enter(GameState::Win).with_system(save_recording.system()))
+        .add_system_set(
+            SystemSet::on_enter(GameState::LevelSelect).with_system(stop_playback.system()),
+        );
+    }
+}
+
+// feeds a replay's inputs through the movement pipeline, one every `1 / moves_per_second`
+pub struct Playback {
+    replay: Replay,
+    next: usize,
+    timer: Timer,
+}
+
+impl Playback {
+    pub fn new(replay: Replay, moves_per_second: f32) -> Self {
+        Playback {
+            replay,
+            next: 0,
+            timer: Timer::from_seconds(1. / moves_per_second, true),
+        }
+    }
+}
+
+struct Recording {
+    replay: Replay,
+    started: f64,
+}
+
+// skip the title screens and go straight to the replay's level
+fn start_playback(
+    playback: Option<Res<Playback>>,
+    mut selected: ResMut<Selected>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if let Some(playback) = playback {
+        *selected = Selected(GridLocation { x: 0, y: 0 }, playback.replay.level.clone());
+        state.set(GameState::InGame).ok();
+    }
+}
+
+fn play_inputs(
+    time: Res<Time>,
+    snake_parts: Res<SnakeParts>,
+    playback: Option<ResMut<Playback>>,
+    buffer: Res<InputBuffer>,
+    mut inputs: EventWriter<PlayerInput>,
+) {
+    let mut playback = match playback {
+        Some(playback) => playback,
+        None => return,
+    };
+
+    // wait for the scene to spawn, and never drop a recorded input
+    if snake_parts.0.is_empty() || buffer.is_full() {
+        return;
+    }
+
+    playback.timer.tick(time.delta());
+    if !playback.timer.just_finished() {
+        return;
+    }
+
+    if let Some(step) = playback.replay.steps.get(playback.next) {
+        inputs.send(step.input);
+        playback.next += 1;
+    }
+}
+
+fn stop_playback(mut commands: Commands) {
+    commands.remove_resource::<Playback>();
+}
+
+fn start_recording(mut commands: Commands, time: Res<Time>, selected: Res<Selected>) {
+    commands.insert_resource(Recording {
+        replay: Replay::new(selected.1.clone()),
+        started: time.seconds_since_startup(),
+    });
+}
+
+fn record(
+    time: Res<Time>,
+    actions: Res<Actions>,
+    playback: Option<Res<Playback>>,
+    recording: Option<ResMut<Recording>>,
+    mut accepted: EventReader<AcceptedInput>,
+) {
+    let mut recording = match recording {
+        Some(recording) => recording,
+        None => return,
+    };
+
+    let at = time.seconds_since_startup() - recording.started;
+    for AcceptedInput(input) in accepted.iter() {
+        recording
+            .replay
+            .steps
+            .push(ReplayStep { input: *input, at });
+    }
+
+    if playback.is_none() && actions.just_pressed(Action::SaveReplay) {
+        save_replay(&recording.replay);
+    }
+}
+
+fn save_recording(recording: Res<Recording>, playback: Option<Res<Playback>>) {
+    // don't record a recording
+    if playback.is_none() {
+        save_replay(&recording.replay);
+    }
+}
+
+fn save_replay(replay: &Replay) {
+    let data = match replay.to_json() {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Failed to serialize replay. Error was {}", e);
+            return;
+        }
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = format!("{}/{}_{}.replay.json", REPLAY_DIR, replay.level.0, seconds);
+
+        match std::fs::create_dir_all(REPLAY_DIR).and_then(|_| std::fs::write(&path, data)) {
+            Ok(_) => println!("Saved replay to {}", path),
+            Err(e) => eprintln!("Failed to save replay to {}. Error was {}", path, e),
+        }
+    }
+
+    // only the latest replay of each level fits in local storage
+    #[cfg(target_arch = "wasm32")]
+    {
+        let storage = web_sys::window()
+            .expect("should have a Window")
+            .local_storage()
+            .expect("should have a Storage")
+            .expect("should have a Storage");
+
+        let key = format!("replay_{}", replay.level.0);
+        if let Err(e) = storage.set_item(&key, data.as_str()) {
+            eprintln!("Failed to save replay to storage. Error was {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+
+use crate::controls::{key_name, Action, Actions, KeyBindings, Prompt, ACTIONS};
+use crate::manifest::Packs;
+use crate::save::{self, BeatLevels, CustomProgress, Stats};
+use crate::GameState;
+
+// lists each action's keys; confirm on one and press a key to change it
+pub(crate) struct RebindPlugin;
+
+impl Plugin for RebindPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Rebinding>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Controls).with_system(enter_controls.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Controls)
+                    .with_system(update_controls.system())
+                    .with_system(draw_bindings.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Controls).with_system(exit_controls.system()),
+            );
+    }
+}
+
+struct ControlsScreen;
+
+// the row for one of `ACTIONS`
+struct BindingRow(usize);
+
+#[derive(Default)]
+struct Rebinding {
+    selected: usize,
+    // the next key pressed is bound to the selected action
+    waiting: bool,
+}
+
+fn enter_controls(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    *rebinding = Rebinding::default();
+
+    let font = asset_server.load("fonts/AsepriteFont.ttf");
+    let text = |value: &str, font_size: f32| TextBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+            Default::default(),
+        ),
+        ..Default::default()
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                justify_content: JustifyContent::Center,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.add(Color::rgb(0.15, 0.15, 0.15).into()),
+            ..Default::default()
+        })
+        .insert(ControlsScreen)
+        .with_children(|root| {
+            root.spawn_bundle(text("CONTROLS", 48.0));
+            for (index, _) in ACTIONS.iter().enumerate() {
+                root.spawn_bundle(text("", 24.0)).insert(BindingRow(index));
+            }
+
+            for (template, action) in [
+                ("{} TO CHANGE", Action::Confirm),
+                ("{} FOR DEFAULTS", Action::Reset),
+                ("{} TO GO BACK", Action::Back),
+            ]
+            .iter()
+            {
+                root.spawn_bundle(text("", 24.0)).insert(Prompt {
+                    template: *template,
+                    action: *action,
+                });
+            }
+        });
+}
+
+fn update_controls(
+    mut state: ResMut<State<GameState>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut actions: ResMut<Actions>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<KeyBindings>,
+) {
+    if rebinding.waiting {
+        if let Some(key) = keyboard_input.get_just_pressed().next() {
+            bindings.bind(ACTIONS[rebinding.selected].0, *key);
+            rebinding.waiting = false;
+        } else if actions.just_pressed(Action::Back) {
+            // from a gamepad, since any key would have been bound
+            rebinding.waiting = false;
+        }
+        return;
+    }
+
+    if actions.just_pressed(Action::Up) {
+        rebinding.selected = (rebinding.selected + ACTIONS.len() - 1) % ACTIONS.len();
+    } else if actions.just_pressed(Action::Down) {
+        rebinding.selected = (rebinding.selected + 1) % ACTIONS.len();
+    } else if actions.just_pressed(Action::Confirm) {
+        // starting next frame, so this press isn't taken as the new key
+        rebinding.waiting = true;
+    } else if actions.just_pressed(Action::Reset) {
+        *bindings = KeyBindings::default();
+    } else if actions.just_pressed(Action::Back) {
+        actions.clear();
+        state.set(GameState::StartScreen).ok();
+    }
+}
+
+fn draw_bindings(
+    rebinding: Res<Rebinding>,
+    bindings: Res<KeyBindings>,
+    mut rows: Query<(&BindingRow, &mut Text)>,
+) {
+    if !rebinding.is_changed() && !bindings.is_changed() {
+        return;
+    }
+
+    for (BindingRow(index), mut text) in rows.iter_mut() {
+        let (action, label) = ACTIONS[*index];
+        let keys = if rebinding.waiting && rebinding.selected == *index {
+            "PRESS A KEY".to_string()
+        } else {
+            let names: Vec<String> = bindings.keys(action).map(key_name).collect();
+            if names.is_empty() {
+                "-".to_string()
+            } else {
+                names.join(" ")
+            }
+        };
+
+        let section = &mut text.sections[0];
+        section.value = format!("{}  {}", label, keys);
+        section.style.color = if rebinding.selected == *index {
+            Color::rgb(1.0, 0.85, 0.3)
+        } else {
+            Color::WHITE
+        };
+    }
+}
+
+fn exit_controls(
+    mut commands: Commands,
+    packs: Res<Packs>,
+    beat_levels: Res<BeatLevels>,
+    stats: Res<Stats>,
+    custom: Res<CustomProgress>,
+    bindings: Res<KeyBindings>,
+    q: Query<Entity, With<ControlsScreen>>,
+) {
+    for e in q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    save::save_progress(&packs, &beat_levels, &stats, &custom, &bindings);
+}
@@ -0,0 +1,156 @@
+// Recorded play sessions: every accepted input, with the level it was made in
+// and when. Replays can be played back in game or checked against the rules
+// without opening a window.
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::history::History;
+use crate::level::Level;
+use crate::rules::{Outcome, PuzzleState};
+use crate::{LevelId, PlayerInput};
+
+#[derive(Serialize, Deserialize)]
+enum ReplayFile {
+    V1(Replay),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub level: LevelId,
+    pub steps: Vec<ReplayStep>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub input: PlayerInput,
+    // seconds since the level started
+    pub at: f64,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "couldn't read replay: {}", e),
+            ReplayError::Json(e) => write!(f, "couldn't parse replay: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    pub fn new(level: LevelId) -> Self {
+        Replay {
+            level,
+            steps: vec![],
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, ReplayError> {
+        let data = fs::read_to_string(path).map_err(ReplayError::Io)?;
+        Replay::from_json(&data).map_err(ReplayError::Json)
+    }
+
+    pub fn from_json(data: &str) -> serde_json::Result<Replay> {
+        match serde_json::from_str(data)? {
+            ReplayFile::V1(replay) => Ok(replay),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(&ReplayFile::V1(self.clone()))
+    }
+
+    pub fn moves(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|step| matches!(step.input, PlayerInput::Move(_)))
+            .count()
+    }
+
+    // play the inputs through the rules; history inputs behave like they do in game
+    pub fn wins(&self, level: &Level) -> bool {
+        let mut history: History<PuzzleState> = History::new(level.state.clone());
+
+        for step in self.steps.iter() {
+            match step.input {
+                PlayerInput::Move(direction) => {
+                    let mut state = history.current().clone();
+                    match state.apply_move(&level.board, direction) {
+                        Outcome::Won => return true,
+                        Outcome::Blocked => {}
+                        Outcome::Moved | Outcome::Fallen => history.push(state),
+                    }
+                }
+                PlayerInput::Undo => {
+                    history.undo();
+                }
+                PlayerInput::Redo => {
+                    history.redo();
+                }
+                PlayerInput::SwitchBranch => {
+                    history.switch_branch();
+                }
+                PlayerInput::Reset => {
+                    history.reset();
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Replay, ReplayStep};
+    use crate::level::Level;
+    use crate::rules::{Board, PuzzleState};
+    use crate::{Direction, GridLocation, LevelId, PlayerInput};
+
+    fn loc(x: i32, y: i32) -> GridLocation {
+        GridLocation { x, y }
+    }
+
+    fn step(input: PlayerInput) -> ReplayStep {
+        ReplayStep { input, at: 0. }
+    }
+
+    #[test]
+    fn replays_survive_a_round_trip_and_still_win() {
+        let level = Level {
+            board: Board::new((-1..4).map(|x| loc(x, -1)).collect()),
+            state: PuzzleState::new(
+                vec![loc(0, 1), loc(0, 0), loc(1, 0), loc(1, 1), loc(2, 1)],
+                vec![],
+                vec![],
+            ),
+        };
+
+        let mut replay = Replay::new(LevelId(3));
+        replay.steps = vec![
+            step(PlayerInput::Move(Direction::Up)),
+            step(PlayerInput::Undo),
+            step(PlayerInput::Move(Direction::Right)),
+        ];
+
+        let replay = Replay::from_json(&replay.to_json().unwrap()).unwrap();
+        assert_eq!(replay.level, LevelId(3));
+        assert_eq!(replay.moves(), 2);
+        assert!(replay.wins(&level));
+
+        let mut without_undo = replay.clone();
+        without_undo.steps.remove(1);
+        assert!(!without_undo.wins(&level));
+    }
+}
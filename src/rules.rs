@@ -0,0 +1,338 @@
+// Engine-independent puzzle rules.
+//
+// Everything in here works on plain grid locations so that levels can be
+// played without a window. Tools and tests drive a whole level through
+// `PuzzleState::apply_move`; the Bevy systems take one step of it a frame
+// and only draw what the step changed.
+
+use std::collections::HashSet;
+
+use crate::{Direction, GridLocation};
+
+// once any part of the snake is below this, no more moves are accepted
+pub const FALL_CUTOFF: i32 = -40;
+
+// gravity stops searching for ground below this
+pub const FALL_FLOOR: i32 = -50;
+
+#[derive(Clone, Debug, Default)]
+pub struct Board {
+    pub grounds: HashSet<GridLocation>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PuzzleState {
+    // head first, tail last
+    pub snake: Vec<GridLocation>,
+    pub foods: Vec<GridLocation>,
+    pub poisons: Vec<GridLocation>,
+}
+
+// the food eaten, and where the snake grew a segment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Eaten {
+    pub food: usize,
+    pub segment: usize,
+}
+
+// the poison eaten, and which segment the snake lost
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Poisoned {
+    pub poison: usize,
+    pub segment: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    // head ran into ground or its own body; nothing changed
+    Blocked,
+    Moved,
+    Won,
+    // some part of the snake has dropped past FALL_CUTOFF
+    Fallen,
+}
+
+impl Board {
+    pub fn new(grounds: HashSet<GridLocation>) -> Self {
+        Board { grounds }
+    }
+}
+
+impl PuzzleState {
+    pub fn new(
+        snake: Vec<GridLocation>,
+        foods: Vec<GridLocation>,
+        poisons: Vec<GridLocation>,
+    ) -> Self {
+        PuzzleState {
+            snake,
+            foods,
+            poisons,
+        }
+    }
+
+    pub fn apply_move(&mut self, board: &Board, direction: Direction) -> Outcome {
+        if has_fallen(&self.snake) {
+            return Outcome::Fallen;
+        }
+
+        if !self.step(board, direction) {
+            return Outcome::Blocked;
+        }
+
+        self.settle(board);
+
+        if has_fallen(&self.snake) {
+            Outcome::Fallen
+        } else if is_won(&self.snake) {
+            Outcome::Won
+        } else {
+            Outcome::Moved
+        }
+    }
+
+    // moves the head one cell and each segment into the one ahead of it,
+    // without settling; false if the head can't go there
+    pub fn step(&mut self, board: &Board, direction: Direction) -> bool {
+        if self.snake.is_empty() || has_fallen(&self.snake) {
+            return false;
+        }
+
+        let proposed_location = self.snake[0].clone() + offset(direction);
+
+        if blocked_cells(&self.snake, &board.grounds).contains(&proposed_location) {
+            return false;
+        }
+
+        for index in (1..self.snake.len()).rev() {
+            self.snake[index] = self.snake[index - 1].clone();
+        }
+        self.snake[0] = proposed_location;
+        true
+    }
+
+    // drops the whole snake until some part of it is on ground; returns how
+    // far (zero or negative)
+    pub fn fall(&mut self, board: &Board) -> i32 {
+        let snake_fall = fall_distance(&self.snake, &board.grounds);
+        for grid_location in self.snake.iter_mut() {
+            grid_location.y += snake_fall;
+        }
+        snake_fall
+    }
+
+    // food under the head is eaten and the snake grows a segment where its
+    // tail is
+    pub fn eat_food(&mut self) -> Option<Eaten> {
+        let head_location = self.snake.first()?;
+        let food = self.foods.iter().position(|f| f == head_location)?;
+        self.foods.remove(food);
+
+        let tail_location = self.snake.last().expect("tail exists").clone();
+        let segment = growth_index(self.snake.len());
+        self.snake.insert(segment, tail_location);
+        Some(Eaten { food, segment })
+    }
+
+    // poison under the head is eaten; the snake loses a segment and its tail
+    // moves up to where that was
+    pub fn eat_poison(&mut self) -> Option<Poisoned> {
+        let head_location = self.snake.first()?;
+        let poison = self.poisons.iter().position(|p| p == head_location)?;
+        self.poisons.remove(poison);
+
+        let segment = shrink_index(self.snake.len());
+        let removed = self.snake.remove(segment);
+        if self.snake.len() > 1 {
+            *self.snake.last_mut().expect("tail exists") = removed;
+        }
+        Some(Poisoned { poison, segment })
+    }
+
+    // gravity, then food, then poison, until nothing changes.
+    // mirrors the order the game systems run in across frames.
+    pub fn settle(&mut self, board: &Board) {
+        loop {
+            let fell = self.fall(board) != 0;
+            let ate = self.eat_food().is_some();
+            let poisoned = self.eat_poison().is_some();
+
+            if !(fell || ate || poisoned) {
+                return;
+            }
+        }
+    }
+
+    pub fn is_won(&self) -> bool {
+        is_won(&self.snake)
+    }
+}
+
+pub fn offset(direction: Direction) -> GridLocation {
+    match direction {
+        Direction::Up => GridLocation { x: 0, y: 1 },
+        Direction::Down => GridLocation { x: 0, y: -1 },
+        Direction::Left => GridLocation { x: -1, y: 0 },
+        Direction::Right => GridLocation { x: 1, y: 0 },
+    }
+}
+
+// cells the head may not move into. `snake` is head first.
+pub fn blocked_cells(
+    snake: &[GridLocation],
+    grounds: &HashSet<GridLocation>,
+) -> HashSet<GridLocation> {
+    let mut tmp = HashSet::new();
+
+    if snake.len() > 3 {
+        // exclude head; exclude tail + second to last
+        for grid_location in snake[1..snake.len() - 2].iter() {
+            tmp.insert(grid_location.clone());
+        }
+    } else if snake.len() == 2 {
+        tmp.insert(snake[1].clone());
+    } else if snake.len() == 3 {
+        tmp.insert(snake[1].clone());
+        tmp.insert(snake[2].clone());
+    }
+
+    for ground in grounds.iter() {
+        tmp.insert(ground.clone());
+    }
+
+    tmp
+}
+
+// how far the whole snake drops (zero or negative) before some part lands on ground
+pub fn fall_distance<'a>(
+    snake: impl IntoIterator<Item = &'a GridLocation>,
+    grounds: &HashSet<GridLocation>,
+) -> i32 {
+    let mut snake_fall = None;
+
+    for snake_grid_location in snake {
+        let mut distance = -1;
+
+        while !grounds.contains(&GridLocation {
+            x: snake_grid_location.x,
+            y: snake_grid_location.y + distance,
+        }) && snake_grid_location.y + distance > FALL_FLOOR
+        {
+            distance -= 1;
+        }
+
+        snake_fall = Some(snake_fall.unwrap_or(i32::MIN).max(distance + 1));
+    }
+
+    snake_fall.unwrap_or(0)
+}
+
+// where a new segment goes after eating food, given the current length
+pub fn growth_index(len: usize) -> usize {
+    match len {
+        1 => 1,
+        _ => len - 1,
+    }
+}
+
+// which segment is removed after eating poison, given the current length
+pub fn shrink_index(len: usize) -> usize {
+    match len {
+        1 => 0,
+        2 => 1,
+        _ => len - 2,
+    }
+}
+
+pub fn has_fallen(snake: &[GridLocation]) -> bool {
+    snake
+        .iter()
+        .any(|grid_location| grid_location.y < FALL_CUTOFF)
+}
+
+pub fn is_won(snake: &[GridLocation]) -> bool {
+    snake.len() > 2 && snake.first() == snake.last()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::iter::FromIterator;
+
+    use super::{Board, Outcome, PuzzleState};
+    use crate::{Direction, GridLocation};
+
+    fn at(x: i32, y: i32) -> GridLocation {
+        GridLocation { x, y }
+    }
+
+    fn floor(from: i32, to: i32, y: i32) -> Board {
+        Board::new(HashSet::from_iter((from..=to).map(|x| at(x, y))))
+    }
+
+    #[test]
+    fn blocked_by_ground_and_body() {
+        let board = Board::new(HashSet::from_iter(vec![at(0, -1), at(1, -1), at(2, 0)]));
+
+        let mut state = PuzzleState::new(vec![at(1, 0), at(0, 0)], vec![], vec![]);
+        assert_eq!(state.apply_move(&board, Direction::Right), Outcome::Blocked);
+        assert_eq!(state.apply_move(&board, Direction::Down), Outcome::Blocked);
+        assert_eq!(state.apply_move(&board, Direction::Left), Outcome::Blocked);
+        assert_eq!(state.snake, vec![at(1, 0), at(0, 0)]);
+    }
+
+    #[test]
+    fn gravity_drops_whole_snake() {
+        let board = floor(1, 1, -3);
+
+        let mut state = PuzzleState::new(vec![at(1, 0), at(0, 0)], vec![], vec![]);
+        assert_eq!(state.apply_move(&board, Direction::Up), Outcome::Moved);
+        assert_eq!(state.snake, vec![at(1, -1), at(1, -2)]);
+    }
+
+    #[test]
+    fn food_grows_before_tail() {
+        let board = floor(0, 4, -1);
+
+        let mut state = PuzzleState::new(vec![at(1, 0), at(0, 0)], vec![at(2, 0)], vec![]);
+        assert_eq!(state.apply_move(&board, Direction::Right), Outcome::Moved);
+        assert_eq!(state.snake, vec![at(2, 0), at(1, 0), at(1, 0)]);
+        assert!(state.foods.is_empty());
+
+        assert_eq!(state.apply_move(&board, Direction::Right), Outcome::Moved);
+        assert_eq!(state.snake, vec![at(3, 0), at(2, 0), at(1, 0)]);
+    }
+
+    #[test]
+    fn poison_pulls_tail_forward() {
+        let board = floor(0, 4, -1);
+
+        let mut state =
+            PuzzleState::new(vec![at(2, 0), at(1, 0), at(0, 0)], vec![], vec![at(3, 0)]);
+        assert_eq!(state.apply_move(&board, Direction::Right), Outcome::Moved);
+        assert_eq!(state.snake, vec![at(3, 0), at(2, 0)]);
+        assert!(state.poisons.is_empty());
+    }
+
+    #[test]
+    fn eating_own_tail_wins() {
+        let board = floor(-2, 2, -1);
+
+        let mut state = PuzzleState::new(
+            vec![at(0, 1), at(0, 0), at(1, 0), at(1, 1), at(2, 1)],
+            vec![],
+            vec![],
+        );
+        assert_eq!(state.apply_move(&board, Direction::Right), Outcome::Won);
+        assert!(state.is_won());
+    }
+
+    #[test]
+    fn falling_out_of_the_level() {
+        let board = Board::default();
+
+        let mut state = PuzzleState::new(vec![at(1, 0), at(0, 0)], vec![], vec![]);
+        assert_eq!(state.apply_move(&board, Direction::Right), Outcome::Fallen);
+        assert_eq!(state.apply_move(&board, Direction::Right), Outcome::Fallen);
+    }
+}
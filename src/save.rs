@@ -0,0 +1,436 @@
+use bevy::prelude::*;
+use chrono::{DateTime, Utc};
+
+use serde::{Deserialize, Serialize};
+
+use std::io::BufReader;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+use std::{fs::File, io::Write};
+
+use crate::controls::KeyBindings;
+use crate::manifest::Packs;
+use crate::LevelId;
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub(crate) struct BeatLevels(pub(crate) HashSet<LevelId>);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub(crate) struct Stats(pub(crate) HashMap<LevelId, LevelStats>);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub(crate) struct LevelStats {
+    pub(crate) best_moves: Option<u32>,
+    // seconds
+    pub(crate) best_time: Option<f64>,
+    pub(crate) attempts: u32,
+    pub(crate) undos: u32,
+    pub(crate) resets: u32,
+    pub(crate) first_completed: Option<DateTime<Utc>>,
+}
+
+// custom levels get fresh ids every run, so their progress is kept by file name
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub(crate) struct CustomProgress {
+    beat_levels: HashSet<String>,
+    stats: HashMap<String, LevelStats>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SaveState {
+    V1(SaveStateV1),
+    V2(SaveStateV2),
+    V3(SaveStateV3),
+    V4(SaveStateV4),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveStateV1 {
+    beat_levels: BeatLevels,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveStateV2 {
+    beat_levels: BeatLevels,
+    stats: Stats,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveStateV3 {
+    beat_levels: BeatLevels,
+    stats: Stats,
+    custom: CustomProgress,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct SaveStateV4 {
+    beat_levels: BeatLevels,
+    stats: Stats,
+    custom: CustomProgress,
+    bindings: KeyBindings,
+}
+
+impl SaveState {
+    // older saves have nothing to lose; they just start without stats, custom
+    // levels or their own key bindings
+    fn into_latest(self) -> SaveStateV4 {
+        match self {
+            SaveState::V1(v) => SaveStateV4 {
+                beat_levels: v.beat_levels,
+                ..Default::default()
+            },
+            SaveState::V2(v) => SaveStateV4 {
+                beat_levels: v.beat_levels,
+                stats: v.stats,
+                ..Default::default()
+            },
+            SaveState::V3(v) => SaveStateV4 {
+                beat_levels: v.beat_levels,
+                stats: v.stats,
+                custom: v.custom,
+                ..Default::default()
+            },
+            SaveState::V4(v) => v,
+        }
+    }
+}
+
+impl SaveStateV4 {
+    // progress for custom levels that aren't around this run stays in `CustomProgress`
+    fn into_progress(self, packs: &Packs) -> (BeatLevels, Stats, CustomProgress, KeyBindings) {
+        let mut beat_levels = self.beat_levels;
+        let mut stats = self.stats;
+
+        for (id, file) in packs.custom_levels() {
+            if self.custom.beat_levels.contains(file) {
+                beat_levels.0.insert(id.clone());
+            }
+            if let Some(level_stats) = self.custom.stats.get(file) {
+                stats.0.insert(id.clone(), level_stats.clone());
+            }
+        }
+
+        (beat_levels, stats, self.custom, self.bindings)
+    }
+
+    fn from_progress(
+        packs: &Packs,
+        beat_levels: &BeatLevels,
+        stats: &Stats,
+        custom: &CustomProgress,
+        bindings: &KeyBindings,
+    ) -> SaveStateV4 {
+        let mut save_state = SaveStateV4 {
+            beat_levels: beat_levels.clone(),
+            stats: stats.clone(),
+            custom: custom.clone(),
+            bindings: bindings.clone(),
+        };
+
+        for (id, file) in packs.custom_levels() {
+            if save_state.beat_levels.0.remove(id) {
+                save_state.custom.beat_levels.insert(file.to_string());
+            }
+            if let Some(level_stats) = save_state.stats.0.remove(id) {
+                save_state
+                    .custom
+                    .stats
+                    .insert(file.to_string(), level_stats);
+            }
+        }
+
+        save_state
+    }
+}
+
+const SAVE_FILE: &str = "0.sav";
+
+pub(crate) fn load_progress(mut commands: Commands, packs: Res<Packs>) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let save_state = match File::open(Path::new(SAVE_FILE)) {
+            Ok(file) => {
+                let reader = BufReader::new(file);
+                match serde_json::from_reader::<_, SaveState>(reader) {
+                    Ok(v) => v.into_latest(),
+                    Err(e) => {
+                        eprintln!("Failed to deser {}. Err was {}", SAVE_FILE, e);
+                        SaveStateV4::default()
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to open {}. Err was {}", SAVE_FILE, e);
+                SaveStateV4::default()
+            }
+        };
+
+        let (beat_levels, stats, custom, bindings) = save_state.into_progress(&packs);
+        commands.insert_resource(beat_levels);
+        commands.insert_resource(stats);
+        commands.insert_resource(custom);
+        commands.insert_resource(bindings);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let storage = web_sys::window()
+            .expect("should have a Window")
+            .local_storage()
+            .expect("should have a Storage")
+            .expect("should have a Storage");
+
+        let save_state = match storage.get_item(SAVE_FILE) {
+            Ok(maybe) => match maybe {
+                Some(save_state_string) => {
+                    match serde_json::from_str::<SaveState>(&save_state_string) {
+                        Ok(v) => v.into_latest(),
+                        Err(e) => {
+                            eprintln!("Failed to deser savestate. Error was {:?}", e);
+                            SaveStateV4::default()
+                        }
+                    }
+                }
+                None => {
+                    eprintln!("No savestate found.");
+                    SaveStateV4::default()
+                }
+            },
+            Err(e) => {
+                eprintln!("Failed to load from local storage. Error was {:?}", e);
+                SaveStateV4::default()
+            }
+        };
+
+        let (beat_levels, stats, custom, bindings) = save_state.into_progress(&packs);
+        commands.insert_resource(beat_levels);
+        commands.insert_resource(stats);
+        commands.insert_resource(custom);
+        commands.insert_resource(bindings);
+    }
+}
+
+pub(crate) fn save_progress(
+    packs: &Packs,
+    beat_levels: &BeatLevels,
+    stats: &Stats,
+    custom: &CustomProgress,
+    bindings: &KeyBindings,
+) {
+    let wrapped = SaveState::V4(SaveStateV4::from_progress(
+        packs,
+        beat_levels,
+        stats,
+        custom,
+        bindings,
+    ));
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        match serde_json::to_vec(&wrapped) {
+            Ok(data) => {
+                match File::create(SAVE_FILE) {
+                    Ok(mut f) => match f.write_all(&data) {
+                        Ok(_) => {
+                            dbg!("Saved to {}", SAVE_FILE);
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to save to {}, error was {}", SAVE_FILE, e);
+                        }
+                    },
+                    Err(e) => {
+                        eprintln!("Failed to create {}. Error was {}", SAVE_FILE, e);
+                    }
+                };
+            }
+            Err(e) => {
+                eprintln!("Failed to serialize. Error was {}", e);
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        match serde_json::to_string(&wrapped) {
+            Ok(data) => {
+                let storage = web_sys::window()
+                    .expect("should have a Window")
+                    .local_storage()
+                    .expect("should have a Storage")
+                    .expect("should have a Storage");
+
+                match storage.set_item(SAVE_FILE, data.as_str()) {
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("Failed to save to storage. Error was {:?}", e);
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to create {}. Error was {:?}", SAVE_FILE, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::KeyCode;
+    use chrono::{TimeZone, Utc};
+
+    use std::{collections::HashSet, iter::FromIterator};
+
+    use super::{
+        BeatLevels, CustomProgress, LevelStats, SaveState, SaveStateV1, SaveStateV2, SaveStateV4,
+        Stats,
+    };
+    use crate::controls::{Action, KeyBindings};
+    use crate::manifest::{LevelEntry, Manifest, Pack, Packs, CUSTOM_IDS};
+    use crate::LevelId;
+
+    fn custom_pack(files: &[&str]) -> Packs {
+        let levels = files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| LevelEntry {
+                id: LevelId(CUSTOM_IDS + i),
+                title: file.to_string(),
+                file: file.to_string(),
+                author: String::new(),
+                par: None,
+            })
+            .collect();
+
+        Packs(vec![Pack {
+            dir: "levels".to_string(),
+            manifest: Manifest {
+                name: "CUSTOM".to_string(),
+                levels,
+            },
+            custom: true,
+        }])
+    }
+
+    #[test]
+    fn it_works() {
+        let levels = HashSet::from_iter(vec![LevelId(0), LevelId(3), LevelId(10)]);
+
+        let save_state = SaveState::V1(SaveStateV1 {
+            beat_levels: BeatLevels(levels.clone()),
+        });
+
+        let sav = dbg!(serde_json::to_string(&save_state));
+        assert!(sav.is_ok());
+
+        let sav = sav.expect("it worked");
+
+        let data: Result<SaveState, _> = serde_json::from_str(&sav);
+
+        let data = data.expect("it works");
+
+        match data {
+            SaveState::V1(data) => {
+                assert_eq!(data.beat_levels.0, levels);
+            }
+            _ => panic!("expected a V1 save"),
+        }
+    }
+
+    #[test]
+    fn v2_round_trips_stats() {
+        let levels = HashSet::from_iter(vec![LevelId(2)]);
+        let mut stats = Stats::default();
+        stats.0.insert(
+            LevelId(2),
+            LevelStats {
+                best_moves: Some(12),
+                best_time: Some(31.5),
+                attempts: 3,
+                undos: 7,
+                resets: 1,
+                first_completed: Some(Utc.ymd(2021, 6, 1).and_hms(12, 0, 0)),
+            },
+        );
+
+        let save_state = SaveState::V2(SaveStateV2 {
+            beat_levels: BeatLevels(levels.clone()),
+            stats: stats.clone(),
+        });
+
+        let sav = serde_json::to_string(&save_state).expect("it worked");
+        let data = serde_json::from_str::<SaveState>(&sav)
+            .expect("it works")
+            .into_latest();
+
+        assert_eq!(data.beat_levels.0, levels);
+        assert_eq!(data.stats, stats);
+    }
+
+    #[test]
+    fn v4_round_trips_key_bindings() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::Up, KeyCode::Z);
+
+        let save_state = SaveState::V4(SaveStateV4 {
+            bindings: bindings.clone(),
+            ..Default::default()
+        });
+
+        let sav = serde_json::to_string(&save_state).expect("it worked");
+        let data = serde_json::from_str::<SaveState>(&sav)
+            .expect("it works")
+            .into_latest();
+
+        assert_eq!(data.bindings, bindings);
+    }
+
+    #[test]
+    fn v1_migrates_to_v2() {
+        // written by the V1 game
+        let sav = r#"{"V1":{"beat_levels":[0,3,10]}}"#;
+
+        let data = serde_json::from_str::<SaveState>(sav)
+            .expect("it works")
+            .into_latest();
+
+        assert_eq!(
+            data.beat_levels.0,
+            HashSet::from_iter(vec![LevelId(0), LevelId(3), LevelId(10)])
+        );
+        assert_eq!(data.stats, Stats::default());
+    }
+
+    #[test]
+    fn custom_progress_is_saved_by_file_name() {
+        let packs = custom_pack(&["mine.scn.ron"]);
+        let beat_levels = BeatLevels(HashSet::from_iter(vec![LevelId(0), LevelId(CUSTOM_IDS)]));
+        let mut stats = Stats::default();
+        stats.0.insert(LevelId(CUSTOM_IDS), LevelStats::default());
+
+        let save_state = SaveStateV4::from_progress(
+            &packs,
+            &beat_levels,
+            &stats,
+            &CustomProgress::default(),
+            &KeyBindings::default(),
+        );
+        assert_eq!(
+            save_state.beat_levels.0,
+            HashSet::from_iter(vec![LevelId(0)])
+        );
+        assert!(save_state.custom.beat_levels.contains("mine.scn.ron"));
+        assert!(save_state.stats.0.is_empty());
+
+        // a new level sorts first next run, so the old one's id moves along
+        let packs = custom_pack(&["another.scn.ron", "mine.scn.ron"]);
+        let (beat_levels, stats, custom, _) = save_state.into_progress(&packs);
+        assert_eq!(
+            beat_levels.0,
+            HashSet::from_iter(vec![LevelId(0), LevelId(CUSTOM_IDS + 1)])
+        );
+        assert!(stats.0.contains_key(&LevelId(CUSTOM_IDS + 1)));
+        assert_eq!(custom.stats.len(), 1);
+    }
+}
@@ -0,0 +1,210 @@
+// Breadth-first search over snake/food/poison states using the same rules as the game.
+//
+// Every move costs the same, so the first winning state found is a minimum-move
+// solution. Exhausting the reachable states without winning proves the level
+// can't be solved.
+
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::level::Level;
+use crate::rules::{Outcome, PuzzleState};
+use crate::Direction;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+#[derive(Clone, Debug)]
+pub struct SolverLimits {
+    pub max_nodes: usize,
+    pub max_time: Option<Duration>,
+}
+
+impl Default for SolverLimits {
+    fn default() -> Self {
+        SolverLimits {
+            max_nodes: 2_000_000,
+            max_time: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Solution {
+    Solved(Vec<Direction>),
+    // every reachable state was visited and none of them won
+    Unsolvable,
+    // hit a node or time limit before finishing the search
+    GaveUp,
+}
+
+#[derive(Clone, Debug)]
+pub struct SolverReport {
+    pub solution: Solution,
+    pub explored: usize,
+    pub elapsed: Duration,
+}
+
+struct Node {
+    state: PuzzleState,
+    parent: Option<(usize, Direction)>,
+}
+
+pub fn solve(level: &Level, limits: &SolverLimits) -> SolverReport {
+    let start = Instant::now();
+
+    // search from where the player first sees the snake, once anything
+    // under it at load has been eaten and it has landed
+    let mut initial = level.state.clone();
+    initial.settle(&level.board);
+
+    let mut visited = HashSet::new();
+    visited.insert(initial.clone());
+    let mut nodes = vec![Node {
+        state: initial,
+        parent: None,
+    }];
+
+    let mut frontier = VecDeque::new();
+    frontier.push_back(0);
+
+    let report = |solution, explored| SolverReport {
+        solution,
+        explored,
+        elapsed: start.elapsed(),
+    };
+
+    if nodes[0].state.is_won() {
+        return report(Solution::Solved(vec![]), 1);
+    }
+
+    while let Some(index) = frontier.pop_front() {
+        if nodes.len() >= limits.max_nodes {
+            return report(Solution::GaveUp, nodes.len());
+        }
+        if let Some(max_time) = limits.max_time {
+            if start.elapsed() > max_time {
+                return report(Solution::GaveUp, nodes.len());
+            }
+        }
+
+        for direction in DIRECTIONS.iter() {
+            let mut state = nodes[index].state.clone();
+
+            match state.apply_move(&level.board, *direction) {
+                Outcome::Blocked | Outcome::Fallen => continue,
+                Outcome::Won => {
+                    let mut moves = vec![*direction];
+                    let mut current = index;
+                    while let Some((parent, direction)) = nodes[current].parent {
+                        moves.push(direction);
+                        current = parent;
+                    }
+                    moves.reverse();
+
+                    return report(Solution::Solved(moves), nodes.len());
+                }
+                Outcome::Moved => {}
+            }
+
+            if visited.contains(&state) {
+                continue;
+            }
+            visited.insert(state.clone());
+
+            frontier.push_back(nodes.len());
+            nodes.push(Node {
+                state,
+                parent: Some((index, *direction)),
+            });
+        }
+    }
+
+    report(Solution::Unsolvable, nodes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::{solve, Solution, SolverLimits};
+    use crate::level::Level;
+    use crate::rules::{Board, PuzzleState};
+    use crate::{Direction, GridLocation};
+
+    fn loc(x: i32, y: i32) -> GridLocation {
+        GridLocation { x, y }
+    }
+
+    #[test]
+    fn finds_the_shortest_win() {
+        let level = Level {
+            board: Board::new((-1..4).map(|x| loc(x, -1)).collect()),
+            state: PuzzleState::new(
+                vec![loc(0, 1), loc(0, 0), loc(1, 0), loc(1, 1), loc(2, 1)],
+                vec![],
+                vec![],
+            ),
+        };
+
+        match solve(&level, &SolverLimits::default()).solution {
+            Solution::Solved(moves) => assert!(matches!(moves[..], [Direction::Right])),
+            other => panic!("expected a solution, got {:?}", other),
+        }
+    }
+
+    // two segments can never meet, and there's no food to grow on
+    fn too_short() -> Level {
+        Level {
+            board: Board::new((0..3).map(|x| loc(x, -1)).collect()),
+            state: PuzzleState::new(vec![loc(1, 0), loc(0, 0)], vec![], vec![]),
+        }
+    }
+
+    #[test]
+    fn proves_a_level_unsolvable() {
+        let report = solve(&too_short(), &SolverLimits::default());
+        assert!(matches!(report.solution, Solution::Unsolvable));
+        assert!(report.explored > 1);
+    }
+
+    // three food in a row for a snake too short to win without them
+    fn hungry(ground: Range<i32>) -> Level {
+        Level {
+            board: Board::new(ground.map(|x| loc(x, -1)).collect()),
+            state: PuzzleState::new(
+                vec![loc(1, 0), loc(0, 0)],
+                (3..6).map(|x| loc(x, 0)).collect(),
+                vec![],
+            ),
+        }
+    }
+
+    #[test]
+    fn searches_from_where_the_snake_lands() {
+        // over the pit it could reach the food, but it falls out on load
+        let report = solve(&hungry(2..7), &SolverLimits::default());
+        assert!(matches!(report.solution, Solution::Unsolvable));
+        assert_eq!(report.explored, 1);
+
+        assert!(matches!(
+            solve(&hungry(0..7), &SolverLimits::default()).solution,
+            Solution::Solved(_)
+        ));
+    }
+
+    #[test]
+    fn gives_up_at_the_node_limit() {
+        let limits = SolverLimits {
+            max_nodes: 40,
+            max_time: None,
+        };
+        let report = solve(&hungry(0..7), &limits);
+        assert!(matches!(report.solution, Solution::GaveUp));
+        assert!(report.explored >= 40);
+    }
+}
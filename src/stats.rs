@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use chrono::Utc;
+
+use crate::controls::KeyBindings;
+use crate::history::History;
+use crate::manifest::Packs;
+use crate::par;
+use crate::playback::Playback;
+use crate::save::{self, BeatLevels, CustomProgress, Stats};
+use crate::{AcceptedInput, GameState, LevelId, PlayerInput, Selected, SnakeMovementLabel};
+
+// keeps per-level stats up to date and saves them with the rest of the progress
+pub(crate) struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::InGame).with_system(start_attempt.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(track_inputs.system())
+                .after(SnakeMovementLabel),
+        )
+        .add_system_set(SystemSet::on_enter(GameState::Win).with_system(record_win.system()))
+        .add_system_set(
+            SystemSet::on_enter(GameState::LevelSelect).with_system(save_attempt.system()),
+        )
+        .add_system_set(
+            SystemSet::on_enter(GameState::LevelSelect).with_system(clear_rating.system()),
+        );
+    }
+}
+
+struct WinRating;
+
+struct Attempt {
+    level: LevelId,
+    started: f64,
+    // moves on the current line of play; mirrors the undo history
+    moves: History<u32>,
+}
+
+fn start_attempt(
+    mut commands: Commands,
+    time: Res<Time>,
+    selected: Res<Selected>,
+    playback: Option<Res<Playback>>,
+    mut stats: ResMut<Stats>,
+) {
+    if playback.is_some() {
+        return;
+    }
+
+    stats.0.entry(selected.1.clone()).or_default().attempts += 1;
+
+    commands.insert_resource(Attempt {
+        level: selected.1.clone(),
+        started: time.seconds_since_startup(),
+        moves: History::new(0),
+    });
+}
+
+fn track_inputs(
+    attempt: Option<ResMut<Attempt>>,
+    mut stats: ResMut<Stats>,
+    mut accepted: EventReader<AcceptedInput>,
+) {
+    let mut attempt = match attempt {
+        Some(attempt) => attempt,
+        None => return,
+    };
+    let level_stats = stats.0.entry(attempt.level.clone()).or_default();
+
+    for AcceptedInput(input) in accepted.iter() {
+        match input {
+            PlayerInput::Move(_) => {
+                let moves = *attempt.moves.current() + 1;
+                attempt.moves.push(moves);
+            }
+            PlayerInput::Undo => {
+                level_stats.undos += 1;
+                attempt.moves.undo();
+            }
+            PlayerInput::Redo => {
+                attempt.moves.redo();
+            }
+            PlayerInput::SwitchBranch => {
+                attempt.moves.switch_branch();
+            }
+            PlayerInput::Reset => {
+                level_stats.resets += 1;
+                attempt.moves.reset();
+            }
+        }
+    }
+}
+
+fn record_win(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    packs: Res<Packs>,
+    attempt: Option<Res<Attempt>>,
+    mut beat_levels: ResMut<BeatLevels>,
+    mut stats: ResMut<Stats>,
+    custom: Res<CustomProgress>,
+    bindings: Res<KeyBindings>,
+) {
+    // replays don't count
+    let attempt = match attempt {
+        Some(attempt) => attempt,
+        None => return,
+    };
+
+    let moves = *attempt.moves.current();
+    let seconds = time.seconds_since_startup() - attempt.started;
+
+    let level_stats = stats.0.entry(attempt.level.clone()).or_default();
+    level_stats.best_moves = Some(level_stats.best_moves.map_or(moves, |best| best.min(moves)));
+    level_stats.best_time = Some(
+        level_stats
+            .best_time
+            .map_or(seconds, |best| best.min(seconds)),
+    );
+    level_stats.first_completed.get_or_insert_with(Utc::now);
+
+    beat_levels.0.insert(attempt.level.clone());
+    save::save_progress(&packs, &beat_levels, &stats, &custom, &bindings);
+
+    let rating = match packs.par(&attempt.level) {
+        Some(par) => format!(
+            "{} MOVES  PAR {}  {}",
+            moves,
+            par,
+            par::star_text(par::stars(moves, par))
+        ),
+        None => format!("{} MOVES", moves),
+    };
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(16.0),
+                    left: Val::Px(16.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                rating,
+                TextStyle {
+                    font: asset_server.load("fonts/AsepriteFont.ttf"),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(WinRating);
+
+    commands.remove_resource::<Attempt>();
+}
+
+fn clear_rating(mut commands: Commands, q: Query<Entity, With<WinRating>>) {
+    for e in q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+// quitting a level still counts its attempts, undos and resets
+fn save_attempt(
+    mut commands: Commands,
+    packs: Res<Packs>,
+    attempt: Option<Res<Attempt>>,
+    beat_levels: Res<BeatLevels>,
+    stats: Res<Stats>,
+    custom: Res<CustomProgress>,
+    bindings: Res<KeyBindings>,
+) {
+    if attempt.is_some() {
+        save::save_progress(&packs, &beat_levels, &stats, &custom, &bindings);
+        commands.remove_resource::<Attempt>();
+    }
+}
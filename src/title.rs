@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+
+use crate::controls::{Action, Actions, Prompt};
+use crate::touch::TouchButton;
+use crate::{AudioCue, GameState};
+
+pub(crate) struct TitlePlugin;
+
+impl Plugin for TitlePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Szunami).with_system(enter_szunami.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Szunami).with_system(update_szunami.system()),
+        )
+        .add_system_set(SystemSet::on_exit(GameState::Szunami).with_system(exit_szunami.system()))
+        .add_system_set(
+            SystemSet::on_enter(GameState::StartScreen).with_system(enter_title_screen.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::StartScreen).with_system(update_title_screen.system()),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::StartScreen).with_system(exit_title_screen.system()),
+        );
+    }
+}
+
+struct Logo;
+
+fn enter_szunami(
+    mut commands: Commands,
+
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+
+    mut audio_cues: EventWriter<AudioCue>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands.spawn_bundle(UiCameraBundle::default());
+    dbg!("entering start menu!");
+    let logo = asset_server.load("sprites/drafts/logo-Sheet.png");
+    let logo = TextureAtlas::from_grid(logo, Vec2::new(371.0, 96.0), 5, 1);
+    let logo = texture_atlases.add(logo);
+
+    audio_cues.send(AudioCue::Szunami);
+
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: logo,
+            ..Default::default()
+        })
+        .insert(Timer::from_seconds(0.1, true))
+        .insert(Logo);
+}
+
+fn update_szunami(
+    time: Res<Time>,
+    mut state: ResMut<State<GameState>>,
+
+    mut q: Query<(&mut TextureAtlasSprite, &mut Timer), With<Logo>>,
+) {
+    for (mut sprite, mut timer) in q.iter_mut() {
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            sprite.index = (sprite.index + 1) % 5;
+        }
+    }
+
+    if time.seconds_since_startup() > 3. {
+        state.set(GameState::StartScreen).ok();
+    }
+}
+
+fn exit_szunami(mut commands: Commands, mut q: Query<Entity, With<Logo>>) {
+    dbg!("exiting start menu");
+    for e in q.iter_mut() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+struct Title;
+
+fn enter_title_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+
+    mut materials: ResMut<Assets<ColorMaterial>>,
+
+    mut clear_color: ResMut<ClearColor>,
+
+    mut audio_cues: EventWriter<AudioCue>,
+) {
+    audio_cues.send(AudioCue::Homescreen);
+
+    *clear_color = ClearColor(Color::rgb(87. / 255., 114. / 255., 119. / 255.));
+
+    let logo = asset_server.load("sprites/drafts/title/snake.png");
+    let logo = TextureAtlas::from_grid(logo, Vec2::new(128.0, 96.0), 31, 1);
+    let logo = texture_atlases.add(logo);
+
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: logo,
+            transform: Transform::from_scale(4. * Vec3::ONE),
+            ..Default::default()
+        })
+        .insert(Title)
+        .insert(Timer::from_seconds(0.1, true));
+
+    let title_material = asset_server.load("sprites/drafts/title/title.png").into();
+
+    let xform = Transform {
+        translation: Vec3::new(0., 230., 0.),
+        scale: 2. * Vec3::ONE,
+        ..Default::default()
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite::new(Vec2::new(204., 52.)),
+            material: materials.add(title_material),
+            transform: xform,
+            ..Default::default()
+        })
+        .insert(Title);
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                justify_content: JustifyContent::Center,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.add(Color::rgba(0.0, 0.0, 0.0, 0.0).into()),
+            ..Default::default()
+        })
+        .insert(Title)
+        // tapping anywhere starts
+        .insert(TouchButton(Action::Confirm))
+        .with_children(|root| {
+            root.spawn_bundle(TextBundle {
+                text: Text {
+                    sections: vec![TextSection {
+                        value: "enter to start".to_string(),
+                        style: TextStyle {
+                            font: asset_server.load("fonts/AsepriteFont.ttf"),
+                            font_size: 40.0,
+                            color: Color::WHITE,
+                        },
+                    }],
+                    ..Default::default()
+                },
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        bottom: Val::Px(64.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Prompt {
+                template: "{} to start",
+                action: Action::Confirm,
+            })
+            .insert(Title);
+
+            root.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "c for controls",
+                    TextStyle {
+                        font: asset_server.load("fonts/AsepriteFont.ttf"),
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                    },
+                    Default::default(),
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        bottom: Val::Px(32.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Prompt {
+                template: "{} for controls",
+                action: Action::Controls,
+            })
+            .insert(Title);
+        });
+}
+
+fn update_title_screen(
+    mut state: ResMut<State<GameState>>,
+
+    mut actions: ResMut<Actions>,
+    time: Res<Time>,
+
+    mut q: Query<(&mut TextureAtlasSprite, &mut Timer), With<Title>>,
+) {
+    for (mut sprite, mut timer) in q.iter_mut() {
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            sprite.index = (sprite.index + 1) % 31;
+        }
+    }
+
+    if actions.just_pressed(Action::Confirm) {
+        actions.clear();
+        state.set(GameState::LevelSelect).ok();
+    } else if actions.just_pressed(Action::Controls) {
+        actions.clear();
+        state.set(GameState::Controls).ok();
+    }
+}
+
+fn exit_title_screen(mut commands: Commands, q: Query<Entity, With<Title>>) {
+    for e in q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
@@ -0,0 +1,303 @@
+// Mouse and touch controls, for playing the web build on a phone.
+//
+// A swipe presses the direction it goes in, and tapping an on-screen button
+// presses its action, through the same `Actions` as the keyboard, so every
+// screen takes them without knowing. The buttons only show once the screen
+// has been touched or clicked; that first tap just brings them up.
+
+use bevy::prelude::*;
+
+use crate::controls::{Action, Actions, ActionsLabel, Device};
+use crate::GameState;
+
+pub(crate) struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            read_touches.system().after(ActionsLabel),
+        )
+        .add_system(show_buttons.system())
+        .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(spawn_buttons.system()))
+        // the back button stays up over the win screen, and the level select
+        // gets one of its own, which leaving it despawns with its other nodes
+        .add_system_set(
+            SystemSet::on_enter(GameState::LevelSelect)
+                .with_system(despawn_buttons.system())
+                .with_system(spawn_back_button.system()),
+        );
+    }
+}
+
+// how far a finger has to move to swipe rather than tap, in pixels
+const SWIPE_DISTANCE: f32 = 48.0;
+
+const BUTTON_SIZE: f32 = 64.0;
+const MARGIN: f32 = 16.0;
+
+// tapping this presses `0`
+pub(crate) struct TouchButton(pub(crate) Action);
+
+// one of the buttons drawn over a level
+struct OnScreenButton;
+
+// (action, label, column, row) with the bottom left button at 0, 0
+const DPAD: [(Action, &str, f32, f32); 4] = [
+    (Action::Up, "^", 1., 2.),
+    (Action::Left, "<", 0., 1.),
+    (Action::Right, ">", 2., 1.),
+    (Action::Down, "v", 1., 0.),
+];
+
+// stacked up the right hand side
+const BUTTONS: [(Action, &str); 3] = [
+    (Action::Back, "BACK"),
+    (Action::Reset, "RESET"),
+    (Action::Undo, "UNDO"),
+];
+
+// the direction of a drag, if it went far enough; `delta` is y up
+fn swipe_direction(delta: Vec2) -> Option<Action> {
+    if delta.length() < SWIPE_DISTANCE {
+        None
+    } else if delta.x.abs() > delta.y.abs() {
+        Some(if delta.x > 0. {
+            Action::Right
+        } else {
+            Action::Left
+        })
+    } else {
+        Some(if delta.y > 0. {
+            Action::Up
+        } else {
+            Action::Down
+        })
+    }
+}
+
+// presses the action of every button at `at`, in the same coordinates as
+// the cursor; true if there was one
+fn tap(
+    at: Vec2,
+    actions: &mut Actions,
+    buttons: &Query<(&TouchButton, &Node, &GlobalTransform, &Visible)>,
+) -> bool {
+    let mut hit = false;
+    for (TouchButton(action), node, transform, visible) in buttons.iter() {
+        let offset = at - transform.translation.truncate();
+        if offset.x.abs() > node.size.x / 2. || offset.y.abs() > node.size.y / 2. {
+            continue;
+        }
+
+        // a hidden button only shows itself
+        if visible.is_visible {
+            actions.press(*action, Device::Touch);
+        }
+        hit = true;
+    }
+    actions.device = Device::Touch;
+    hit
+}
+
+fn read_touches(
+    mouse_input: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    mut actions: ResMut<Actions>,
+    // where the mouse went down, unless that was on a button
+    mut drag_start: Local<Option<Vec2>>,
+    buttons: Query<(&TouchButton, &Node, &GlobalTransform, &Visible)>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let cursor = window.cursor_position();
+
+    // browsers send taps and drags as the mouse
+    if mouse_input.just_pressed(MouseButton::Left) {
+        let on_button = cursor.map_or(false, |at| tap(at, &mut actions, &buttons));
+        *drag_start = if on_button { None } else { cursor };
+    }
+    if mouse_input.just_released(MouseButton::Left) {
+        let swipe = drag_start
+            .take()
+            .zip(cursor)
+            .and_then(|(start, end)| swipe_direction(end - start));
+        if let Some(action) = swipe {
+            actions.press(action, Device::Touch);
+        }
+    }
+
+    for touch in touches.iter_just_released() {
+        // bevy only counts touches up from the bottom on mobile
+        let (mut at, mut delta) = (touch.position(), touch.distance());
+        if !cfg!(any(target_os = "android", target_os = "ios")) {
+            at.y = window.height() - at.y;
+            delta.y = -delta.y;
+        }
+
+        match swipe_direction(delta) {
+            Some(action) => actions.press(action, Device::Touch),
+            None => {
+                tap(at, &mut actions, &buttons);
+            }
+        }
+    }
+}
+
+fn spawn_button(
+    commands: &mut Commands,
+    font: &Handle<Font>,
+    material: &Handle<ColorMaterial>,
+    (action, label): (Action, &str),
+    width: f32,
+    position: Rect<Val>,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position,
+                size: Size::new(Val::Px(width), Val::Px(BUTTON_SIZE)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: material.clone(),
+            visible: Visible {
+                is_visible: false,
+                is_transparent: true,
+            },
+            ..Default::default()
+        })
+        .insert(TouchButton(action))
+        .insert(OnScreenButton)
+        .with_children(|button| {
+            button.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                    },
+                    Default::default(),
+                ),
+                visible: Visible {
+                    is_visible: false,
+                    is_transparent: true,
+                },
+                ..Default::default()
+            });
+        });
+}
+
+// the right hand side button in `row`, counting up from the bottom
+fn side_button(row: usize) -> Rect<Val> {
+    Rect {
+        right: Val::Px(MARGIN),
+        bottom: Val::Px(MARGIN + row as f32 * (BUTTON_SIZE + MARGIN)),
+        ..Default::default()
+    }
+}
+
+fn spawn_buttons(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let font = asset_server.load("fonts/AsepriteFont.ttf");
+    let material = materials.add(Color::rgba(0.15, 0.15, 0.15, 0.6).into());
+
+    for (action, label, column, row) in DPAD.iter() {
+        spawn_button(
+            &mut commands,
+            &font,
+            &material,
+            (*action, *label),
+            BUTTON_SIZE,
+            Rect {
+                left: Val::Px(MARGIN + column * BUTTON_SIZE),
+                bottom: Val::Px(MARGIN + row * BUTTON_SIZE),
+                ..Default::default()
+            },
+        );
+    }
+
+    for (row, button) in BUTTONS.iter().enumerate() {
+        spawn_button(
+            &mut commands,
+            &font,
+            &material,
+            *button,
+            2. * BUTTON_SIZE,
+            side_button(row),
+        );
+    }
+}
+
+// where it is over a level, so it's in the same place on every screen
+fn spawn_back_button(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let font = asset_server.load("fonts/AsepriteFont.ttf");
+    let material = materials.add(Color::rgba(0.15, 0.15, 0.15, 0.6).into());
+    spawn_button(
+        &mut commands,
+        &font,
+        &material,
+        (Action::Back, "BACK"),
+        2. * BUTTON_SIZE,
+        side_button(0),
+    );
+}
+
+// while the last thing used was a finger or the mouse; only back once the level is won,
+// and on the level select, where it's the only button
+fn show_buttons(
+    actions: Res<Actions>,
+    state: Res<State<GameState>>,
+    mut buttons: Query<(&TouchButton, &mut Visible, &Children), With<OnScreenButton>>,
+    mut labels: Query<&mut Visible, Without<OnScreenButton>>,
+) {
+    for (TouchButton(action), mut visible, children) in buttons.iter_mut() {
+        let shown = actions.device == Device::Touch
+            && (*state.current() == GameState::InGame || *action == Action::Back);
+        if visible.is_visible == shown {
+            continue;
+        }
+
+        visible.is_visible = shown;
+        for child in children.iter() {
+            if let Ok(mut label) = labels.get_mut(*child) {
+                label.is_visible = shown;
+            }
+        }
+    }
+}
+
+fn despawn_buttons(mut commands: Commands, q: Query<Entity, With<OnScreenButton>>) {
+    for e in q.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::swipe_direction;
+    use crate::controls::Action;
+
+    use bevy::math::Vec2;
+
+    #[test]
+    fn long_drags_swipe_the_way_they_mostly_go() {
+        assert_eq!(swipe_direction(Vec2::new(10.0, -5.0)), None);
+        assert_eq!(swipe_direction(Vec2::new(60.0, -40.0)), Some(Action::Right));
+        assert_eq!(swipe_direction(Vec2::new(-20.0, 90.0)), Some(Action::Up));
+        assert_eq!(swipe_direction(Vec2::new(0.0, -48.0)), Some(Action::Down));
+    }
+}
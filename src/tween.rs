@@ -0,0 +1,132 @@
+// Time-based movement from cell to cell.
+//
+// Each queued move takes a set time however fast frames come, so the snake
+// looks the same on a fast desktop and in a browser. A slow frame can finish
+// several moves at once; every move ends exactly on its cell.
+
+use bevy::math::Vec2;
+
+use crate::{GridLocation, GRID_HEIGHT, GRID_WIDTH};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    // starts quick and settles into the cell
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    // how much of the way there a move is, `t` of the way through its time
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Easing> {
+        match name {
+            "linear" => Some(Easing::Linear),
+            "ease-out" => Some(Easing::EaseOut),
+            "ease-in-out" => Some(Easing::EaseInOut),
+            _ => None,
+        }
+    }
+}
+
+// how sprites move to the cells queued for them
+#[derive(Clone, Copy, Debug)]
+pub struct MoveAnimation {
+    pub tiles_per_second: f32,
+    pub easing: Easing,
+}
+
+impl Default for MoveAnimation {
+    // about as fast as the old 8 pixels a frame at 60fps
+    fn default() -> Self {
+        MoveAnimation {
+            tiles_per_second: 15.0,
+            easing: Easing::EaseOut,
+        }
+    }
+}
+
+// the move a sprite is part way through
+#[derive(Default)]
+pub(crate) struct Tween {
+    // where the sprite was when the move started
+    from: Option<Vec2>,
+    // share of the move's time used up
+    progress: f32,
+}
+
+impl MoveAnimation {
+    // where a sprite at `at` is `seconds` later; cells it reaches are popped
+    // off the front of `queue`
+    pub(crate) fn advance(
+        &self,
+        at: Vec2,
+        queue: &mut Vec<GridLocation>,
+        tween: &mut Tween,
+        mut seconds: f32,
+    ) -> Vec2 {
+        let mut at = at;
+        while let Some(cell) = queue.first() {
+            let target = Vec2::new(GRID_WIDTH * cell.x as f32, GRID_HEIGHT * cell.y as f32);
+            let from = *tween.from.get_or_insert(at);
+
+            // gravity can queue a fall of several cells as one move
+            let tiles = ((target - from) / Vec2::new(GRID_WIDTH, GRID_HEIGHT)).length();
+            let left = (1.0 - tween.progress) * tiles / self.tiles_per_second;
+            if seconds < left {
+                tween.progress += seconds * self.tiles_per_second / tiles;
+                return from + (target - from) * self.easing.apply(tween.progress);
+            }
+
+            seconds -= left;
+            at = target;
+            *tween = Tween::default();
+            queue.remove(0);
+        }
+        at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Easing, MoveAnimation, Tween};
+    use crate::{GridLocation, GRID_WIDTH};
+
+    use bevy::math::Vec2;
+
+    #[test]
+    fn moves_take_the_same_time_at_any_frame_rate() {
+        let animation = MoveAnimation {
+            tiles_per_second: 8.0,
+            easing: Easing::Linear,
+        };
+
+        // an eighth of a second, in eight frames at 64fps
+        let mut queue = vec![GridLocation { x: 1, y: 0 }];
+        let mut tween = Tween::default();
+        let mut at = Vec2::new(0.0, 0.0);
+        for frame in 0..8 {
+            assert_eq!(queue.len(), 1, "arrived early, on frame {}", frame);
+            at = animation.advance(at, &mut queue, &mut tween, 1.0 / 64.0);
+        }
+        assert!(queue.is_empty());
+        assert_eq!(at, Vec2::new(GRID_WIDTH, 0.0));
+
+        // one long frame finishes both moves and lands exactly
+        let mut queue = vec![GridLocation { x: 1, y: 0 }, GridLocation { x: 1, y: 3 }];
+        let mut tween = Tween::default();
+        let at = animation.advance(Vec2::new(0.0, 0.0), &mut queue, &mut tween, 1.0);
+        assert!(queue.is_empty());
+        assert_eq!(at, Vec2::new(GRID_WIDTH, 3.0 * GRID_WIDTH));
+
+        assert_eq!(Easing::EaseOut.apply(1.0), 1.0);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+    }
+}